@@ -3,12 +3,12 @@ use crate::{
     errors::*,
     format::{
         blocks::{output::*, *},
-        Attention,
+        Attention, Banner,
     },
 };
 use chrono::{DateTime, Duration, Local};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::{path::PathBuf, sync::mpsc::Sender};
 
 /// The status of a battery.
 #[derive(Clone, PartialEq)]
@@ -62,11 +62,13 @@ pub enum BatteryLevel {
     MinutesLeft(i64),
 }
 
-const SYS_POWER_SUPPLY_BASE_DIR: &str = "/sys/class/power_supply/";
+pub(crate) const SYS_POWER_SUPPLY_BASE_DIR: &str = "/sys/class/power_supply/";
 const MAX_READS: i32 = 15; // used for moving averages
 
 /// Data block for battery reports and estimates
 pub struct BatteryBlock {
+    id: String,
+
     warning_level: BatteryLevel,
     alarm_level: BatteryLevel,
 
@@ -81,6 +83,12 @@ pub struct BatteryBlock {
 
     current_read: Option<BatteryRead>,
     last_read: Option<BatteryRead>,
+
+    banner_sender: Option<Sender<Banner>>,
+    alarm_banner_sent: bool,
+
+    /// Whether the time left (or "Plugged in") is shown. Toggled by clicking the block.
+    show_secondary: bool,
 }
 
 impl BatteryBlock {
@@ -88,6 +96,8 @@ impl BatteryBlock {
     pub fn new(config: BatteryConfig) -> Self {
         let battery = config.battery_id;
         Self {
+            id: String::from("battery"),
+
             warning_level: config.warning_level,
             alarm_level: config.alarm_level,
 
@@ -102,9 +112,20 @@ impl BatteryBlock {
 
             current_read: None,
             last_read: None,
+
+            banner_sender: None,
+            alarm_banner_sent: false,
+
+            show_secondary: true,
         }
     }
 
+    /// Sets the id of the block, which is used as its name. By default, the id is `battery`.
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = id.to_string();
+        self
+    }
+
     fn calculate_new_rate(&mut self, rate_now: f32) {
         if let Some(r) = &self.current_read {
             match &r.status {
@@ -256,11 +277,32 @@ impl BatteryBlock {
             },
         }
     }
+
+    /// Sends a banner the first time the battery reaches its alarm level while discharging.
+    fn send_alarm_banner(&mut self) {
+        let is_discharging = matches!(
+            self.current_read.as_ref().map(|r| &r.status),
+            Some(ChargeStatus::Discharging)
+        );
+        let is_alarm = is_discharging && self.is_alarm();
+
+        if is_alarm && !self.alarm_banner_sent {
+            if let Some(sender) = &self.banner_sender {
+                let _ = sender.send(Banner::new(
+                    self.name(),
+                    "Battery is critically low. Plug in soon!",
+                    10.0,
+                ));
+            }
+        }
+
+        self.alarm_banner_sent = is_alarm;
+    }
 }
 
 impl Block for BatteryBlock {
     fn name(&self) -> &str {
-        "battery"
+        &self.id
     }
 
     fn output(&self) -> Option<BlockOutput> {
@@ -323,10 +365,11 @@ impl Block for BatteryBlock {
                     Attention::Normal
                 };
 
-                let block_text = if let Some(secondary_text) = secondary_text_opt {
-                    BlockText::Pair(primary_text, secondary_text)
-                } else {
-                    BlockText::Single(primary_text)
+                let block_text = match secondary_text_opt {
+                    Some(secondary_text) if self.show_secondary => {
+                        BlockText::Pair(primary_text, secondary_text)
+                    }
+                    _ => BlockText::Single(primary_text),
                 };
                 Some(BlockOutput::new(
                     self.name(),
@@ -386,9 +429,27 @@ impl Block for BatteryBlock {
 
         self.last_read = self.current_read.clone();
 
+        self.send_alarm_banner();
+
         Ok(())
     }
 
+    fn set_banner_sender(&mut self, banner_sender: Sender<Banner>) {
+        self.banner_sender = Some(banner_sender);
+    }
+
+    fn click(&mut self, event: &ClickEvent) -> Result<(), UpdateError> {
+        if event.button == click::MouseButton::Left {
+            self.show_secondary = !self.show_secondary;
+        }
+
+        Ok(())
+    }
+
+    fn click_buttons(&self) -> &'static [click::MouseButton] {
+        &[click::MouseButton::Left]
+    }
+
     fn next_update(&self) -> Option<NextUpdate> {
         Some(NextUpdate::In(Duration::seconds(5)))
     }
@@ -403,7 +464,8 @@ fn get_new_average_rate(
     (avg_rate_now.unwrap_or(0.0) * reads) / (reads + 1.0) + most_recent_read_rate / (reads + 1.0)
 }
 
-const DISCHARGING_ICONS: [char; 11] = [
+/// Icons for a discharging battery, from empty to full.
+pub(crate) const DISCHARGING_ICONS: [char; 11] = [
     '\u{f008e}',
     '\u{f007a}',
     '\u{f007b}',
@@ -416,7 +478,8 @@ const DISCHARGING_ICONS: [char; 11] = [
     '\u{f0082}',
     '\u{f0079}',
 ];
-const CHARGING_ICONS: [char; 11] = [
+/// Icons for a charging battery, from empty to full.
+pub(crate) const CHARGING_ICONS: [char; 11] = [
     '\u{f089f}',
     '\u{f089c}',
     '\u{f0086}',
@@ -1,11 +1,10 @@
-use muse_status::{
-    battery, brightness, config, daemon::Daemon, date, format::blocks::Block, mpris, network,
-    volume, weather,
-};
+use muse_status::{config, daemon::Daemon};
+use std::path::{Path, PathBuf};
 
 fn main() {
     let mut env_args = std::env::args();
     let mut config_path = None;
+    let mut check_only = false;
     while let Some(arg) = env_args.next() {
         if arg == "--config" || arg == "-c" {
             config_path = Some(
@@ -13,52 +12,46 @@ fn main() {
                     .next()
                     .unwrap_or_else(|| panic!("`{}` requires a value", arg)),
             );
+        } else if arg == "check-config" {
+            check_only = true;
         }
     }
 
-    let config = if let Some(path) = config_path {
-        config::Config::from_file(path).unwrap()
+    // only the default config file is created if it's missing, so that a mistyped path isn't
+    // silently filled with defaults
+    let (config_path, is_default_path) = if let Some(path) = config_path {
+        (PathBuf::from(path), false)
     } else {
-        let path = config::default_config_path().unwrap();
-
-        config::Config::from_file(path).unwrap()
+        (config::default_config_path().unwrap(), true)
     };
 
-    #[cfg(debug_assertions)]
-    {
-        eprintln!("daemon config: {:?}", config);
+    if check_only {
+        std::process::exit(check_config(&config_path));
     }
 
-    let battery_block = battery::BatteryBlock::new(config.battery_config.clone());
-    let brightness_block = brightness::BrightnessBlock::new(&config.brightness_id);
-    let date_block = date::DateBlock::new();
-    let network_block = match network::NetworkBlock::new(&config.network_interface_name) {
-        Ok(n) => n,
+    let config_result = if is_default_path {
+        config::Config::from_file_or_create(&config_path)
+    } else {
+        config::Config::from_file(&config_path)
+    };
+    let config = match config_result {
+        Ok(c) => c,
         Err(e) => {
-            eprintln!("couldn't create network block: {}", e);
-            return;
+            eprintln!("{}", e);
+            eprintln!(
+                "try `muse-status-daemon check-config` to find problems in the configuration file"
+            );
+            std::process::exit(1);
         }
     };
-    let mpris_block = mpris::MprisBlock::new();
-    let volume_block = if let Some(sink) = &config.volume_sink {
-        volume::VolumeBlock::new(sink)
-    } else {
-        volume::VolumeBlock::default()
-    };
-    let weather_block = weather::WeatherBlock::new(config.weather_config.clone());
 
-    let blocks: Vec<Box<dyn Block>> = vec![
-        Box::new(date_block),
-        Box::new(weather_block),
-        Box::new(mpris_block),
-        Box::new(brightness_block),
-        Box::new(volume_block),
-        Box::new(network_block),
-        Box::new(battery_block),
-    ];
+    #[cfg(debug_assertions)]
+    {
+        eprintln!("daemon config: {:?}", config);
+    }
 
-    let daemon = Daemon::new(config);
-    match daemon.start(blocks) {
+    let daemon = Daemon::new(config, config_path);
+    match daemon.start() {
         Ok(j) => {
             println!("the daemon is running");
             for handle in j {
@@ -72,3 +65,25 @@ fn main() {
         }
     }
 }
+
+/// Checks the configuration file at `path` and prints any problems found. Returns the exit code:
+/// zero if there were no problems, and one otherwise.
+fn check_config(path: &Path) -> i32 {
+    match config::check::check_file(path) {
+        Ok(diagnostics) if diagnostics.is_empty() => {
+            println!("{}: no problems found", path.display());
+            0
+        }
+        Ok(diagnostics) => {
+            for d in &diagnostics {
+                eprintln!("{}:{}", path.display(), d);
+            }
+            eprintln!("{} problem(s) found", diagnostics.len());
+            1
+        }
+        Err(e) => {
+            eprintln!("couldn't check {}: {}", path.display(), e);
+            1
+        }
+    }
+}
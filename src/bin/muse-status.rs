@@ -1,8 +1,9 @@
 use muse_status::client;
-use muse_status::errors::MuseStatusError;
 
-fn main() -> Result<(), MuseStatusError> {
+fn main() {
     // now isn't this simple :)
-    let client = client::Client::new()?;
-    client.act()
+    if let Err(e) = client::Client::new().and_then(|client| client.act()) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
 }
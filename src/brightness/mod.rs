@@ -1,12 +1,14 @@
 use crate::errors::*;
+use crate::format::animation::Animation;
 use crate::format::blocks::output::*;
 use crate::format::blocks::*;
 use crate::format::Attention;
 use crate::utils;
 use std::path::PathBuf;
 
-const BASE_DIR: &str = "/sys/class/backlight/";
-const BRIGHTNESS_ICONS: [char; 6] = [
+pub(crate) const BASE_DIR: &str = "/sys/class/backlight/";
+/// Icons for each level of brightness, from dimmest to brightest.
+pub(crate) const BRIGHTNESS_ICONS: [char; 6] = [
     '\u{F00DB}',
     '\u{F00DC}',
     '\u{F00DD}',
@@ -17,18 +19,28 @@ const BRIGHTNESS_ICONS: [char; 6] = [
 
 /// BrightnessBlock is a block that contains device brightness information
 pub struct BrightnessBlock {
+    id: String,
     card: String,
     current_brightness: u32,
     max_brightness: u32,
+
+    /// The percentage before the last update, which the shown percentage counts up or down from.
+    previous_percent: Option<u32>,
+
+    /// Whether the brightness is shown as a slider instead of a percentage.
+    slider: bool,
 }
 
 impl BrightnessBlock {
     /// Returns a new BrightnessBlock that reads from the `card` specified
     pub fn new(card: &str) -> Self {
         let mut b = Self {
+            id: String::from("brightness"),
             card: card.to_owned(),
             current_brightness: 0,
             max_brightness: 0,
+            previous_percent: None,
+            slider: false,
         };
 
         // ignore errors
@@ -38,6 +50,19 @@ impl BrightnessBlock {
         b
     }
 
+    /// Sets the id of the block, which is used as its name. By default, the id is `brightness`.
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = id.to_string();
+        self
+    }
+
+    /// Sets whether the brightness is shown as a slider instead of a percentage. By default, it's
+    /// a percentage.
+    pub fn with_slider(mut self, slider: bool) -> Self {
+        self.slider = slider;
+        self
+    }
+
     fn update_max_brightness(&mut self) -> Result<(), UpdateError> {
         let path = &PathBuf::from(BASE_DIR)
             .join(&self.card)
@@ -70,14 +95,20 @@ impl BrightnessBlock {
 
         Ok(())
     }
+
+    /// Returns the brightness as a percentage, or `None` if the max brightness isn't known.
+    fn percent(&self) -> Option<u32> {
+        (self.current_brightness * 100).checked_div(self.max_brightness)
+    }
 }
 
 impl Block for BrightnessBlock {
     fn name(&self) -> &str {
-        "brightness"
+        &self.id
     }
 
     fn update(&mut self) -> Result<(), UpdateError> {
+        self.previous_percent = self.percent();
         self.update_current_brightness()?;
         self.update_max_brightness()?; // because why not
 
@@ -89,19 +120,27 @@ impl Block for BrightnessBlock {
     }
 
     fn output(&self) -> Option<BlockOutput> {
-        if self.max_brightness == 0 {
-            return None;
-        }
-
-        let percent = self.current_brightness * 100 / self.max_brightness;
+        let percent = self.percent()?;
         let icon = get_icon(percent);
-        let block_text = BlockText::Single(format!("{}%", percent));
-        Some(BlockOutput::new(
-            self.name(),
-            Some(icon),
-            block_text,
-            Attention::Dim,
-        ))
+        let block_text = if self.slider {
+            BlockText::Progress {
+                value: percent as f32,
+                min: 0.0,
+                max: 100.0,
+                label: None,
+            }
+        } else {
+            BlockText::Single(format!("{}%", percent))
+        };
+        let output = BlockOutput::new(self.name(), Some(icon), block_text, Attention::Dim);
+
+        // count to the new percentage from the old one
+        match self.previous_percent {
+            Some(old) if old != percent => {
+                Some(output.with_animation(Animation::tween(old as f32, percent as f32)))
+            }
+            _ => Some(output),
+        }
     }
 }
 
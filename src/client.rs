@@ -1,15 +1,26 @@
 use crate::{
     config::{self, Config},
-    daemon::{Collection, DaemonMsg, DataPayload},
-    errors::MuseStatusError,
-    format::{blocks::BlockOutput, Formatter, Mode},
+    daemon::{watch, Collection, DaemonMsg, DataPayload},
+    errors::{BasicError, MuseStatusError, ProtocolError},
+    format::{
+        banner::BannerPlacement,
+        blocks::{click::MouseButton, BlockOutput, ClickEvent},
+        theme::{self, Theme},
+        Attention, Banner, Formatter, Mode,
+    },
+    protocol::{self, Capability, Session},
+    timer::TimerCommand,
+    transport::{DaemonAddr, Stream},
+    utils,
 };
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
-    io::{BufRead, BufReader, Write},
-    net::TcpStream,
-    path::PathBuf,
+    collections::{HashMap, HashSet},
+    io::{BufRead, ErrorKind},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Sender},
+    thread,
+    time::Duration,
 };
 
 /// A Client that connects to the Daemon and receives data.
@@ -41,29 +52,29 @@ impl Client {
         match &self.args.client_msg {
             ClientMsg::Noop => {
                 #[cfg(debug_assertions)]
-                println!("doing nothing; exiting");
+                eprintln!("doing nothing; exiting");
 
                 // girl bye
                 Ok(())
             }
             _ => {
                 #[cfg(debug_assertions)]
-                println!("sending action to daemon: {:?}", self.args.client_msg);
+                eprintln!("sending action to daemon: {:?}", self.args.client_msg);
 
                 // for anything else, we'll need a connection to the daemon.
-                let mut stream = get_daemon_connection(&self.args.config.daemon_addr);
-                stream.write_all(
-                    format!("{}\n", serde_json::to_string(&self.args.client_msg)?).as_bytes(),
-                )?;
+                let stream = send_to_daemon(&self.args.config.daemon_addr, &self.args.client_msg)?;
 
                 // if Subscribe, handle the subscription. if Update, send request and quit.
                 match self.args.client_msg.clone() {
                     ClientMsg::Subscribe(c) => {
                         self.handle_subscription(stream, &c);
                     }
-                    ClientMsg::Update(_) => {
-                        // if Update, the client does not need to maintain its connection
-                        // to the daemon, so we just return
+                    ClientMsg::Update(_)
+                    | ClientMsg::Banner(_)
+                    | ClientMsg::Click(_)
+                    | ClientMsg::Timer { .. } => {
+                        // if Update, Banner, Click or Timer, the client does not need to maintain
+                        // its connection to the daemon, so we just return
                         Ok(())
                     }
                     ClientMsg::Noop => unreachable!(),
@@ -74,77 +85,136 @@ impl Client {
 
     /// If the client should subscribe and output data, handle that. Because this function never
     /// returns, it will take ownership of `self`.
-    pub fn handle_subscription(mut self, mut daemon_conn: TcpStream, collection: &Collection) -> ! {
-        let formatter = Formatter::from_env().unwrap();
+    pub fn handle_subscription(mut self, daemon_conn: Stream, collection: &Collection) -> ! {
+        let mut formatter = std::mem::take(&mut self.args.formatter);
 
-        // if using the json protocol, this header is needed
+        // if using the json protocol, this header is needed. the status bar will send clicks on
+        // our blocks through stdin, which we forward to the daemon
         if let Mode::JsonProtocol = formatter.get_format_mode() {
-            println!("{{\"version\":1}}");
+            println!("{{\"version\":1,\"click_events\":true}}");
             println!("[[]");
+
+            spawn_click_reader(self.args.config.daemon_addr.clone());
         }
 
+        // messages from the daemon are read in a separate thread, so that this one can also
+        // redraw on its own schedule (like when a banner expires, or to play animations)
+        let (event_tx, event_rx) = mpsc::channel::<ClientEvent>();
+        spawn_daemon_reader(daemon_conn, event_tx.clone());
+
+        // the theme is reloaded when its file changes
+        let mut watched_themes = HashSet::new();
+        self.watch_theme(&event_tx, &mut watched_themes);
+
+        let mut animating = false;
         loop {
-            // create a buffered stream, which we'll read from line by line for status outputs
-            let mut buf_stream = BufReader::new(daemon_conn);
-
-            // listen for outputs from the daemon and print them
-            'inner: loop {
-                let mut s = String::new();
-                #[allow(clippy::single_match)]
-                match buf_stream.read_line(&mut s) {
-                    Ok(n) if n > 0 => {
-                        // `s` should be a DaemonMsg
-                        let msg = match serde_json::from_str::<DaemonMsg>(&s) {
-                            Ok(m) => m,
-                            Err(e) => {
-                                eprintln!("{}", e);
-                                break 'inner;
-                            }
-                        };
-
-                        match msg {
-                            // the daemon has an updated output for us. if the data is `Some`
-                            // data, then we'll update it in the status bar. if it's `None`,
-                            // we'll remove it from the status bar
-                            DaemonMsg::NewOutput(msg) => {
-                                if let Some(output) = msg.data() {
-                                    self.data.insert(output.name().clone(), output);
-                                } else {
-                                    self.data.remove(&msg.name());
-                                }
-                            }
+            // `None` means that nothing happened, but it's time to redraw anyway
+            let event = match formatter.next_redraw(animating) {
+                Some(timeout) => event_rx.recv_timeout(timeout).ok(),
+                None => event_rx.recv().ok(),
+            };
 
-                            // the daemon has sent us all the data it has.
-                            DaemonMsg::AllData(a) => {
-                                for output in a {
-                                    self.data.insert(output.name().clone(), output);
-                                }
-                            }
-                        }
+            match event {
+                Some(ClientEvent::Daemon(msg)) => {
+                    let config_reloaded = matches!(msg, DaemonMsg::ConfigReloaded);
+                    self.handle_daemon_msg(msg, &mut formatter);
 
-                        self.echo_output(collection, &formatter);
-                    }
-                    Ok(_) => {
-                        // a non-positive amount of bytes were read in this case, so the stream has
-                        // reached EOF. we'll stop reading from the stream
-                        break 'inner;
+                    // the new config may use a different theme
+                    if config_reloaded {
+                        self.watch_theme(&event_tx, &mut watched_themes);
                     }
-                    Err(e) => {
-                        // an error occurred, so we'll print the error to stderr and stop reading
-                        // from the stream
-                        eprintln!("{}", e);
-                        break 'inner;
+                }
+                Some(ClientEvent::ThemeChanged) => self.reload_theme(&mut formatter),
+                Some(ClientEvent::Disconnected) => {
+                    // if the connection to the daemon is lost, restore it. if the daemon that
+                    // comes back can't talk to us, there's nothing more we can do
+                    match send_to_daemon(&self.args.config.daemon_addr, &self.args.client_msg) {
+                        Ok(c) => spawn_daemon_reader(c, event_tx.clone()),
+                        Err(e) => {
+                            eprintln!("{}", e);
+                            std::process::exit(1);
+                        }
                     }
+
+                    continue;
                 }
+                None => (),
             }
 
-            // if the connection to the daemon is lost, restore it
-            daemon_conn = get_daemon_connection(&self.args.config.daemon_addr);
+            formatter.update_banners();
+            animating = self.echo_output(collection, &formatter);
+        }
+    }
+
+    /// Updates the client's data (or the formatter) with a message from the daemon.
+    fn handle_daemon_msg(&mut self, msg: DaemonMsg, formatter: &mut Formatter) {
+        match msg {
+            // the daemon has an updated output for us. if the data is `Some` data, then we'll
+            // update it in the status bar. if it's `None`, we'll remove it from the status bar
+            // animations are started here, carrying on from the output being replaced
+            DaemonMsg::NewOutput(msg) => {
+                if let Some(mut output) = msg.data() {
+                    output.start_animation(self.data.get(&output.name()));
+                    self.data.insert(output.name().clone(), output);
+                } else {
+                    self.data.remove(&msg.name());
+                }
+            }
+
+            // the daemon has sent us all the data it has, so anything else we have is stale
+            DaemonMsg::AllData(a) => {
+                let old = std::mem::take(&mut self.data);
+                for mut output in a {
+                    output.start_animation(old.get(&output.name()));
+                    self.data.insert(output.name().clone(), output);
+                }
+            }
+
+            // the daemon wants us to show a banner
+            DaemonMsg::Banner(b) => formatter.banner(b),
+
+            // the daemon reloaded its config, so we'll reload ours too (block orders may have
+            // changed). if ours can't be read, keep the old one
+            DaemonMsg::ConfigReloaded => match Config::from_file(&self.args.config_path) {
+                Ok(c) => {
+                    self.args.config = c;
+                    formatter.set_styles(self.args.config.block_styles());
+                    self.reload_theme(formatter);
+                }
+                Err(e) => eprintln!("couldn't reload the configuration file: {}", e),
+            },
+        }
+    }
+
+    /// Reloads the theme into the formatter. If it can't be read, the old one stays in use.
+    fn reload_theme(&self, formatter: &mut Formatter) {
+        if let Err(e) = self.args.apply_theme(formatter) {
+            eprintln!(
+                "the theme couldn't be reloaded, so the old one is still in use: {}",
+                e
+            );
+        }
+    }
+
+    /// Starts watching the file of the theme in use, unless it's already being watched. Themes
+    /// whose directory doesn't exist are built in, so there's nothing to watch.
+    fn watch_theme(&self, event_tx: &Sender<ClientEvent>, watched: &mut HashSet<PathBuf>) {
+        let path = match self.args.theme_name().map(theme::theme_path) {
+            Some(Ok(p)) => p,
+            _ => return,
+        };
+        if !path.parent().is_some_and(Path::exists) || !watched.insert(path.clone()) {
+            return;
+        }
+
+        if let Err(e) = watch::watch_file(&path, event_tx.clone(), || ClientEvent::ThemeChanged) {
+            eprintln!("couldn't watch the theme for changes: {}", e);
         }
     }
 
-    /// Prints formatted output.
-    fn echo_output(&self, collection: &Collection, f: &Formatter) {
+    /// Prints formatted output. Returns true if anything printed is pulsing or animating, in
+    /// which case it should be redrawn for the next frame.
+    fn echo_output(&self, collection: &Collection, f: &Formatter) -> bool {
         let config = &self.args.config;
         let data = match collection {
             Collection::All => DataPayload::ranked(config, &self.data),
@@ -155,7 +225,10 @@ impl Client {
             Collection::Many(names) => DataPayload::from_many(names, &self.data),
         };
 
+        let animating = f.is_animating(&data);
         println!("{}", f.format_data(data));
+
+        animating
     }
 
     // TODO
@@ -165,10 +238,125 @@ impl Client {
     // }
 }
 
+/// Events handled by a subscribed client.
+enum ClientEvent {
+    /// A message from the daemon.
+    Daemon(DaemonMsg),
+
+    /// The connection to the daemon was lost.
+    Disconnected,
+
+    /// The theme file changed.
+    ThemeChanged,
+}
+
+/// Reads messages from the daemon in a new thread and forwards them as `ClientEvent`s. When the
+/// connection is lost, `ClientEvent::Disconnected` is sent and the thread ends.
+fn spawn_daemon_reader(mut daemon_conn: Stream, event_tx: Sender<ClientEvent>) {
+    thread::Builder::new()
+        .name(String::from("daemon reader"))
+        .spawn(move || {
+            loop {
+                match protocol::read_frame_bytes(&mut daemon_conn) {
+                    Ok(Some(bytes)) => {
+                        // the frame should be a DaemonMsg. if it isn't, it's probably a message
+                        // from a newer daemon that we don't know about yet, so we'll skip it
+                        match serde_json::from_slice::<DaemonMsg>(&bytes) {
+                            Ok(m) => {
+                                if event_tx.send(ClientEvent::Daemon(m)).is_err() {
+                                    return;
+                                }
+                            }
+                            Err(e) => eprintln!("ignoring a message from the daemon: {}", e),
+                        }
+                    }
+                    Ok(None) => break, // the stream has reached EOF
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        break;
+                    }
+                }
+            }
+
+            let _ = event_tx.send(ClientEvent::Disconnected);
+        })
+        .unwrap();
+}
+
+/// A click event as sent by i3bar and swaybar through stdin.
+#[derive(Deserialize)]
+struct BarClickEvent {
+    name: Option<String>,
+    button: u32,
+    #[serde(default)]
+    modifiers: Vec<String>,
+    x: Option<i32>,
+    y: Option<i32>,
+    relative_x: Option<i32>,
+    relative_y: Option<i32>,
+    width: Option<i32>,
+    height: Option<i32>,
+}
+
+impl BarClickEvent {
+    /// Converts the event into a `ClickEvent`. Returns `None` if the event doesn't say which block
+    /// was clicked.
+    fn into_click_event(self) -> Option<ClickEvent> {
+        Some(ClickEvent {
+            block_name: self.name?,
+            button: MouseButton::from_x11_code(self.button),
+            modifiers: self.modifiers,
+            position: self.x.zip(self.y),
+            relative_position: self.relative_x.zip(self.relative_y),
+            size: self.width.zip(self.height),
+        })
+    }
+}
+
+/// Reads click events from the status bar in a new thread and forwards them to the daemon.
+///
+/// The status bar sends an infinite json array, one click object per line, so the opening bracket
+/// and the commas between objects are stripped before parsing each line.
+fn spawn_click_reader(addr: DaemonAddr) {
+    thread::Builder::new()
+        .name(String::from("click reader"))
+        .spawn(move || {
+            for line in std::io::stdin().lock().lines() {
+                let line = match line {
+                    Ok(l) => l,
+                    Err(e) => {
+                        eprintln!("couldn't read click events: {}", e);
+                        return;
+                    }
+                };
+
+                let object = line.trim().trim_start_matches(['[', ',']);
+                if object.is_empty() {
+                    continue;
+                }
+
+                let event = match serde_json::from_str::<BarClickEvent>(object) {
+                    Ok(e) => e.into_click_event(),
+                    Err(e) => {
+                        eprintln!("ignoring a click event that couldn't be parsed: {}", e);
+                        continue;
+                    }
+                };
+
+                if let Some(event) = event {
+                    if let Err(e) = send_to_daemon(&addr, &ClientMsg::Click(event)) {
+                        eprintln!("couldn't send click to the daemon: {}", e);
+                    }
+                }
+            }
+        })
+        .unwrap();
+}
+
 /// Polls for a connection to the daemon.
-fn get_daemon_connection(addr: &str) -> TcpStream {
+fn get_daemon_connection(addr: &DaemonAddr) -> Stream {
     loop {
-        if let Ok(s) = TcpStream::connect(addr) {
+        if let Ok(s) = Stream::connect(addr) {
             return s;
         }
 
@@ -176,6 +364,53 @@ fn get_daemon_connection(addr: &str) -> TcpStream {
     }
 }
 
+/// Connects to the daemon and performs the handshake, polling until the daemon is available.
+/// Returns an error if the daemon can't talk to this client.
+fn connect_to_daemon(addr: &DaemonAddr) -> Result<(Stream, Session), MuseStatusError> {
+    loop {
+        let mut stream = get_daemon_connection(addr);
+        stream.set_read_timeout(Some(protocol::HANDSHAKE_TIMEOUT))?;
+
+        match protocol::client_handshake(&mut stream) {
+            Ok(session) => {
+                stream.set_read_timeout(None)?;
+                return Ok((stream, session));
+            }
+            Err(MuseStatusError::Io(e))
+                if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut =>
+            {
+                return Err(ProtocolError::NoHandshake.into())
+            }
+            Err(MuseStatusError::Io(e)) => {
+                // the daemon probably went away mid-handshake, so try again
+                eprintln!("couldn't complete the handshake with the daemon: {}", e);
+                std::thread::sleep(std::time::Duration::from_secs(1));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Connects to the daemon and sends it a message, returning the connection for further use.
+fn send_to_daemon(addr: &DaemonAddr, msg: &ClientMsg) -> Result<Stream, MuseStatusError> {
+    let (mut stream, session) = connect_to_daemon(addr)?;
+
+    if let Some(capability) = msg.required_capability() {
+        if !session.supports(capability) {
+            return Err(MuseStatusError::from(BasicError {
+                message: format!(
+                    "the daemon doesn't support {:?} requests. it may be older than this client",
+                    capability
+                ),
+            }));
+        }
+    }
+
+    protocol::write_frame(&mut stream, msg)?;
+
+    Ok(stream)
+}
+
 /// A payload sent from clients to the daemon.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum ClientMsg {
@@ -185,10 +420,40 @@ pub enum ClientMsg {
     /// Update some part of the client.
     Update(Collection),
 
+    /// Show a banner on every subscribed client.
+    Banner(Banner),
+
+    /// Forward a click on a block to the block.
+    Click(ClickEvent),
+
+    /// Control a timer in a timer block: the one with the id `block` if it's set, or else the
+    /// first one running.
+    Timer {
+        /// The id of the timer block to send the command to.
+        block: Option<String>,
+
+        /// What to do with the timer.
+        command: TimerCommand,
+    },
+
     /// Literally do nothing.
     Noop,
 }
 
+impl ClientMsg {
+    /// Returns the capability the daemon needs to have to handle this message, if any.
+    pub fn required_capability(&self) -> Option<Capability> {
+        match self {
+            Self::Subscribe(_) => Some(Capability::Subscribe),
+            Self::Update(_) => Some(Capability::Update),
+            Self::Banner(_) => Some(Capability::Banners),
+            Self::Click(_) => Some(Capability::Clicks),
+            Self::Timer { .. } => Some(Capability::Timers),
+            Self::Noop => None,
+        }
+    }
+}
+
 impl Default for ClientMsg {
     fn default() -> Self {
         Self::Subscribe(Collection::All)
@@ -201,9 +466,63 @@ struct ClientArgs {
     force: bool,
     formatter: Formatter,
     config: Config,
+    config_path: PathBuf,
+
+    /// The theme passed with `--theme`, which takes precedence over the config's.
+    theme: Option<String>,
+    style_flags: StyleFlags,
+}
+
+/// Colors and the icon font passed as flags, which take precedence over the theme's.
+#[derive(Default)]
+struct StyleFlags {
+    primary_color: Option<String>,
+    secondary_color: Option<String>,
+    warning_color: Option<String>,
+    alarm_color: Option<String>,
+    icon_font: Option<String>,
+}
+
+impl StyleFlags {
+    fn apply(&self, formatter: &mut Formatter) -> Result<(), MuseStatusError> {
+        if let Some(c) = &self.primary_color {
+            formatter.set_primary_color(c)?;
+        }
+        if let Some(c) = &self.secondary_color {
+            formatter.set_secondary_color(c)?;
+        }
+        if let Some(c) = &self.warning_color {
+            formatter.set_warning_color(c)?;
+        }
+        if let Some(c) = &self.alarm_color {
+            formatter.set_alarm_color(c)?;
+        }
+        if let Some(f) = &self.icon_font {
+            formatter.set_icon_font(f);
+        }
+
+        Ok(())
+    }
 }
 
 impl ClientArgs {
+    /// Returns the name of the theme in use, if any: the one passed with `--theme`, or the
+    /// config's.
+    fn theme_name(&self) -> Option<&str> {
+        self.theme.as_deref().or(self.config.theme.as_deref())
+    }
+
+    /// Loads the theme in use into `formatter`, with the style flags applied over it.
+    fn apply_theme(&self, formatter: &mut Formatter) -> Result<(), MuseStatusError> {
+        let theme = match self.theme_name() {
+            Some(name) => theme::load(name)?,
+            None => Theme::default(),
+        };
+
+        formatter.set_theme(theme);
+        self.style_flags.apply(formatter)
+    }
+
     pub fn from_env() -> Result<Self, MuseStatusError> {
         let mut result = Self::default();
 
@@ -213,6 +532,9 @@ impl ClientArgs {
         enum ClientMsgType {
             Subscribe,
             Update,
+            Banner,
+            Click,
+            Timer,
         }
 
         // default values
@@ -220,7 +542,22 @@ impl ClientArgs {
         let mut collection = Collection::All;
         let mut config_path = None;
 
-        let mut args = std::env::args();
+        // banner values
+        let mut banner_words = Vec::new();
+        let mut banner_id = None;
+        let mut banner_duration = Duration::from_secs(5);
+        let mut banner_attention = Attention::Normal;
+        let mut banner_placement = BannerPlacement::Large;
+
+        // click values: the block name, then the button
+        let mut click_words = Vec::new();
+
+        // timer values: the action, the timer's name, then maybe a duration
+        let mut timer_words = Vec::new();
+        let mut timer_block = None;
+
+        // skip the program name
+        let mut args = std::env::args().skip(1);
 
         // parse args
         while let Some(arg) = args.next() {
@@ -230,25 +567,56 @@ impl ClientArgs {
             };
 
             match arg.as_str() {
+                // everything that isn't a flag after `banner` is the banner's text
+                _ if matches!(msg_type, ClientMsgType::Banner) && !arg.starts_with('-') => {
+                    banner_words.push(arg)
+                }
+
+                // likewise, the block name and button follow `click`
+                _ if matches!(msg_type, ClientMsgType::Click) && !arg.starts_with('-') => {
+                    click_words.push(arg)
+                }
+
+                // and the action, name and duration follow `timer`
+                _ if matches!(msg_type, ClientMsgType::Timer) && !arg.starts_with('-') => {
+                    timer_words.push(arg)
+                }
+
                 "sub" | "subscribe" => msg_type = ClientMsgType::Subscribe,
                 "u" | "update" | "n" | "notify" => msg_type = ClientMsgType::Update,
+                "banner" => msg_type = ClientMsgType::Banner,
+                "click" => msg_type = ClientMsgType::Click,
+                "timer" => msg_type = ClientMsgType::Timer,
                 "p" | "primary" => collection = Collection::Primary,
                 "s" | "secondary" => collection = Collection::Secondary,
                 "t" | "tertiary" => collection = Collection::Tertiary,
                 "a" | "all" => collection = Collection::All,
 
                 "-p" | "--primary-color" => {
-                    result.formatter.set_primary_color(&extract_next_value()?)?
+                    result.style_flags.primary_color = Some(extract_next_value()?)
                 }
-                "-s" | "--secondary-color" => result
-                    .formatter
-                    .set_secondary_color(&extract_next_value()?)?,
-                "-i" | "--icon-font" => result.formatter.set_icon_font(&extract_next_value()?),
+                "-s" | "--secondary-color" => {
+                    result.style_flags.secondary_color = Some(extract_next_value()?)
+                }
+                "--warning-color" => result.style_flags.warning_color = Some(extract_next_value()?),
+                "--alarm-color" => result.style_flags.alarm_color = Some(extract_next_value()?),
+                "-i" | "--icon-font" => result.style_flags.icon_font = Some(extract_next_value()?),
+                "--theme" => result.theme = Some(extract_next_value()?),
                 "-m" | "--mode" => result
                     .formatter
                     .set_format_mode(extract_next_value()?.parse()?),
+                "--fps" | "--pulse-fps" => {
+                    result.formatter.set_frame_rate(&extract_next_value()?)?
+                }
                 "-f" | "--force" => result.force = true,
                 "-c" | "--config" => config_path = Some(PathBuf::from(extract_next_value()?)),
+
+                "--id" => banner_id = Some(extract_next_value()?),
+                "--duration" => banner_duration = utils::parse_duration(&extract_next_value()?)?,
+                "--attention" => banner_attention = extract_next_value()?.parse()?,
+                "--block" => timer_block = Some(extract_next_value()?),
+                "--large" => banner_placement = BannerPlacement::Large,
+                "--small" => banner_placement = BannerPlacement::Small,
                 _ => {
                     if arg.starts_with('-') {
                         eprintln!("heads up: `{}` is not a flag muse-status recognizes, but we'll go on anyways", arg)
@@ -263,22 +631,88 @@ impl ClientArgs {
             }
         }
 
-        // if no config path was passed in, try getting the default one
-        let config = if let Some(path) = config_path {
-            Config::from_file(path)?
+        // if no config path was passed in, try getting the default one. only the default one is
+        // created if it's missing, so that a mistyped path isn't silently filled with defaults
+        let (config, config_path) = if let Some(path) = config_path {
+            (Config::from_file(&path)?, path)
         } else {
             let path = config::default_config_path()?;
 
-            Config::from_file(path)?
+            (Config::from_file_or_create(&path)?, path)
         };
 
         result.client_msg = match msg_type {
             ClientMsgType::Subscribe => ClientMsg::Subscribe(collection),
             ClientMsgType::Update => ClientMsg::Update(collection),
+            ClientMsgType::Banner => {
+                if banner_words.is_empty() {
+                    return Err(MuseStatusError::from(String::from(
+                        "`banner` requires some text to show",
+                    )));
+                }
+
+                // without an id, identical banners replace each other
+                let text = banner_words.join(" ");
+                let id = banner_id.unwrap_or_else(|| text.clone());
+                ClientMsg::Banner(
+                    Banner::new(&id, &text, banner_duration.as_secs_f32())
+                        .with_attention(banner_attention)
+                        .with_placement(banner_placement),
+                )
+            }
+            ClientMsgType::Click => match click_words.as_slice() {
+                [block_name] => ClientMsg::Click(ClickEvent::new(block_name, MouseButton::Left)),
+                [block_name, button] => {
+                    ClientMsg::Click(ClickEvent::new(block_name, button.parse()?))
+                }
+                _ => {
+                    return Err(MuseStatusError::from(String::from(
+                        "usage: `click <block> [button]`",
+                    )))
+                }
+            },
+            ClientMsgType::Timer => ClientMsg::Timer {
+                block: timer_block,
+                command: parse_timer_command(&timer_words)?,
+            },
         };
 
         result.config = config;
+        result.config_path = config_path;
+
+        // only subscriptions draw anything, so other messages don't fail over a broken theme
+        if let ClientMsg::Subscribe(_) = result.client_msg {
+            let mut formatter = std::mem::take(&mut result.formatter);
+            result.apply_theme(&mut formatter)?;
+            formatter.set_styles(result.config.block_styles());
+            result.formatter = formatter;
+        }
 
         Ok(result)
     }
 }
+
+/// Parses the words after `timer`, like `start tea 4m` or `pause tea`.
+fn parse_timer_command(words: &[String]) -> Result<TimerCommand, MuseStatusError> {
+    let words = words.iter().map(String::as_str).collect::<Vec<_>>();
+    let command = match words.as_slice() {
+        ["start", name] => TimerCommand::Start {
+            name: name.to_string(),
+            seconds: None,
+        },
+        ["start", name, duration] => TimerCommand::Start {
+            name: name.to_string(),
+            seconds: Some(utils::parse_duration(duration)?.as_secs_f64()),
+        },
+        ["pause", name] => TimerCommand::Pause(name.to_string()),
+        ["resume", name] => TimerCommand::Resume(name.to_string()),
+        ["cancel", name] => TimerCommand::Cancel(name.to_string()),
+        _ => {
+            return Err(MuseStatusError::from(String::from(
+                "usage: `timer start <name> [duration]` or `timer pause|resume|cancel <name>`",
+            )))
+        }
+    };
+
+    Ok(command)
+}
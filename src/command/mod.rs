@@ -0,0 +1,350 @@
+use crate::{
+    config::CommandConfig,
+    errors::*,
+    format::{
+        blocks::{self, output::*, *},
+        Attention,
+    },
+};
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{BufRead, BufReader, Read},
+    os::unix::process::CommandExt,
+    process::{Child, ChildStdout, Command, Stdio},
+    sync::{
+        mpsc::{self, Sender},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time,
+};
+
+/// How a command block runs its command.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandMode {
+    /// The command is run again every interval, and the last line it prints is shown.
+    #[default]
+    Interval,
+
+    /// The command is run once and keeps running. Every line it prints is shown as it comes.
+    Persistent,
+}
+
+/// How a command block reads the lines its command prints.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandFormat {
+    /// Lines are shown as they are. A tab separates the primary and secondary text.
+    #[default]
+    Text,
+
+    /// Lines are JSON objects, like `{"icon": "X", "text": "on call", "attention": "Warning"}`.
+    Json,
+}
+
+/// A line printed by a command in the JSON format.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonLine {
+    icon: Option<char>,
+    text: String,
+    secondary: Option<String>,
+    #[serde(default)]
+    attention: Attention,
+}
+
+/// What a command block shows, as parsed from a line.
+struct Shown {
+    icon: Option<char>,
+    text: BlockText,
+    attention: Attention,
+}
+
+/// A block that shows what a command prints. This is the way to show anything muse-status doesn't
+/// have a block for.
+pub struct CommandBlock {
+    id: String,
+    config: CommandConfig,
+    shown: Option<Shown>,
+
+    /// Set when the daemon stops a persistent block, so that its command isn't restarted.
+    stopped: bool,
+}
+
+impl CommandBlock {
+    /// Returns a new block with the configuration provided.
+    pub fn new(config: CommandConfig) -> Self {
+        Self {
+            id: String::from("command"),
+            config,
+            shown: None,
+            stopped: false,
+        }
+    }
+
+    /// Sets the id of the block, which is used as its name. By default, the id is `command`.
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = id.to_string();
+        self
+    }
+
+    fn to_update_error(&self, message: String) -> UpdateError {
+        UpdateError {
+            block_name: self.name().to_string(),
+            message,
+        }
+    }
+
+    fn spawn(&self) -> Result<(Child, ChildStdout), UpdateError> {
+        // stderr is left alone, so that it ends up wherever the daemon's goes. the command gets
+        // its own process group, so that anything it starts can be killed along with it
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(&self.config.command)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .process_group(0)
+            .spawn()
+            .map_err(|e| self.to_update_error(format!("couldn't run command: {}", e)))?;
+        let stdout = child.stdout.take().unwrap();
+
+        Ok((child, stdout))
+    }
+
+    /// Runs the command to completion, returning what it printed. The command is killed if it
+    /// takes longer than `timeout_seconds`.
+    fn run_once(&self) -> Result<String, UpdateError> {
+        let (mut child, mut stdout) = self.spawn()?;
+
+        // stdout is read on another thread, so that waiting on it can time out
+        let (output_tx, output_rx) = mpsc::channel();
+        thread::spawn(move || {
+            let mut output = String::new();
+            let _ = output_tx.send(stdout.read_to_string(&mut output).map(|_| output));
+        });
+
+        let timeout = time::Duration::from_secs(self.config.timeout_seconds.into());
+        let output = match output_rx.recv_timeout(timeout) {
+            Ok(o) => o.map_err(|e| self.to_update_error(format!("couldn't read output: {}", e))),
+            Err(_) => {
+                kill_group(&mut child);
+                let _ = child.wait();
+                return Err(self.to_update_error(format!(
+                    "command timed out after {} seconds",
+                    self.config.timeout_seconds
+                )));
+            }
+        };
+
+        let status = child
+            .wait()
+            .map_err(|e| self.to_update_error(format!("couldn't wait for command: {}", e)))?;
+        if !status.success() {
+            return Err(self.to_update_error(format!("command failed ({})", status)));
+        }
+
+        output
+    }
+
+    /// Sets what's shown from a line of output. An empty line hides the block.
+    fn show_line(&mut self, line: &str) -> Result<(), UpdateError> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.trim().is_empty() {
+            self.shown = None;
+            return Ok(());
+        }
+
+        let shown = match self.config.format {
+            CommandFormat::Text => Shown {
+                icon: self.config.icon,
+                text: match line.split_once('\t') {
+                    Some((primary, secondary)) => {
+                        BlockText::Pair(primary.to_string(), secondary.to_string())
+                    }
+                    None => BlockText::Single(line.to_string()),
+                },
+                attention: Attention::Normal,
+            },
+            CommandFormat::Json => {
+                let parsed: JsonLine = serde_json::from_str(line).map_err(|e| {
+                    self.to_update_error(format!("couldn't parse `{}`: {}", line, e))
+                })?;
+
+                Shown {
+                    icon: parsed.icon.or(self.config.icon),
+                    text: match parsed.secondary {
+                        Some(secondary) => BlockText::Pair(parsed.text, secondary),
+                        None => BlockText::Single(parsed.text),
+                    },
+                    attention: parsed.attention,
+                }
+            }
+        };
+
+        self.shown = Some(shown);
+        Ok(())
+    }
+
+    /// Runs the command in persistent mode, showing every line it prints until it exits. The
+    /// running command is kept in `child`, so that it can be killed when the block stops.
+    fn read_persistent(
+        block: &Mutex<Self>,
+        child: &Mutex<Option<Child>>,
+        block_sender: &Sender<BlockOutputMsg>,
+    ) -> Result<(), UpdateError> {
+        let stdout = {
+            let mut child = child.lock().unwrap();
+            let block = block.lock().unwrap();
+            if block.stopped {
+                return Ok(());
+            }
+
+            let (spawned, stdout) = block.spawn()?;
+            *child = Some(spawned);
+            stdout
+        };
+
+        for line in BufReader::new(stdout).lines() {
+            let mut block = block.lock().unwrap();
+            let line = line.map_err(|e| block.to_update_error(format!("couldn't read: {}", e)))?;
+
+            if let Err(e) = block.show_line(&line) {
+                println!("{}", e);
+                continue;
+            }
+            if block_sender
+                .send(BlockOutputMsg::new(block.name(), block.output()))
+                .is_err()
+            {
+                return Ok(());
+            }
+        }
+
+        let status = child.lock().unwrap().take().map(|mut c| c.wait());
+        let block = block.lock().unwrap();
+        match status {
+            _ if block.stopped => Ok(()),
+            Some(Ok(s)) => Err(block.to_update_error(format!("command exited ({})", s))),
+            Some(Err(e)) => Err(block.to_update_error(format!("couldn't wait for command: {}", e))),
+            None => Ok(()),
+        }
+    }
+
+    fn run_persistent(
+        self,
+        block_sender: Sender<BlockOutputMsg>,
+    ) -> (Vec<JoinHandle<()>>, Sender<BlockRequest>) {
+        // the command's output is the only thing that updates the block, so requests are only
+        // listened to for when the daemon drops its sender
+        let (request_tx, request_rx) = mpsc::channel::<BlockRequest>();
+        let reader_thread_name = format!("{} command reader", self.name());
+        let request_thread_name = format!("{} request listener", self.name());
+
+        let shared_block = Arc::new(Mutex::new(self));
+        let shared_child = Arc::new(Mutex::new(None));
+
+        let reader_block = shared_block.clone();
+        let reader_child = shared_child.clone();
+        let reader_handle = thread::Builder::new()
+            .name(reader_thread_name)
+            .spawn(move || loop {
+                let result = Self::read_persistent(&reader_block, &reader_child, &block_sender);
+
+                let restart_delay = {
+                    let block = reader_block.lock().unwrap();
+                    if block.stopped {
+                        break;
+                    }
+                    if let Err(e) = result {
+                        println!("{}", e);
+                    }
+
+                    time::Duration::from_secs(block.config.interval_seconds.max(1).into())
+                };
+
+                // wait a bit before restarting the command, in case it fails right away
+                thread::sleep(restart_delay);
+            })
+            .unwrap();
+
+        let request_handle = thread::Builder::new()
+            .name(request_thread_name)
+            .spawn(move || {
+                while request_rx.recv().is_ok() {}
+
+                shared_block.lock().unwrap().stopped = true;
+                if let Some(c) = shared_child.lock().unwrap().as_mut() {
+                    kill_group(c);
+                }
+            })
+            .unwrap();
+
+        (vec![reader_handle, request_handle], request_tx)
+    }
+}
+
+/// Kills a command and everything it started, which share its process group. Killing only `sh`
+/// would leave its children holding stdout open, so reading it would never finish.
+fn kill_group(child: &mut Child) {
+    // SAFETY: kill has no memory safety requirements. the child hasn't been waited on, so its
+    // pid (which is also its process group's id) can't have been reused
+    if unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL) } != 0 {
+        let _ = child.kill();
+    }
+}
+
+impl Block for CommandBlock {
+    fn run(
+        self: Box<Self>,
+        block_sender: Sender<BlockOutputMsg>,
+    ) -> (Vec<JoinHandle<()>>, Sender<BlockRequest>) {
+        match self.config.mode {
+            CommandMode::Interval => blocks::run_update_loop(self, block_sender),
+            CommandMode::Persistent => self.run_persistent(block_sender),
+        }
+    }
+
+    fn name(&self) -> &str {
+        &self.id
+    }
+
+    fn update(&mut self) -> Result<(), UpdateError> {
+        // persistent commands update the block themselves
+        if self.config.mode == CommandMode::Persistent {
+            return Ok(());
+        }
+
+        let output = match self.run_once() {
+            Ok(o) => o,
+            Err(e) => {
+                self.shown = None;
+                return Err(e);
+            }
+        };
+
+        let last_line = output.lines().last().unwrap_or_default().to_string();
+        self.show_line(&last_line)
+    }
+
+    fn next_update(&self) -> Option<NextUpdate> {
+        match self.config.mode {
+            CommandMode::Interval => Some(NextUpdate::In(Duration::seconds(
+                self.config.interval_seconds.max(1).into(),
+            ))),
+            CommandMode::Persistent => None,
+        }
+    }
+
+    fn output(&self) -> Option<BlockOutput> {
+        let shown = self.shown.as_ref()?;
+
+        Some(BlockOutput::new(
+            self.name(),
+            shown.icon,
+            shown.text.clone(),
+            shown.attention.clone(),
+        ))
+    }
+}
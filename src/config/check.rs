@@ -0,0 +1,649 @@
+use super::*;
+use crate::{battery, brightness, format::theme, keyboard, network, reminders, temperature};
+use regex::Regex;
+use serde::de::DeserializeOwned;
+use serde_yaml::Value;
+use std::{fmt, fs};
+use yaml_rust::{
+    parser::{Event, MarkedEventReceiver, Parser},
+    scanner::Marker,
+};
+
+/// A problem found in a config file, with the line and column where it was found.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Diagnostic {
+    /// The line of the problem, starting at 1.
+    pub line: usize,
+
+    /// The column of the problem, starting at 1.
+    pub column: usize,
+
+    /// What the problem is.
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(line: usize, column: usize, message: String) -> Self {
+        Self {
+            line,
+            column,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// Checks the config file at `path`, returning every problem found. An error is only returned if
+/// the file can't be read at all.
+pub fn check_file(path: &Path) -> Result<Vec<Diagnostic>, MuseStatusError> {
+    Ok(check_str(&fs::read_to_string(path)?))
+}
+
+/// Checks the contents of a config file, returning every problem found.
+pub fn check_str(source: &str) -> Vec<Diagnostic> {
+    let mut locations = Locations::default();
+    if let Err(e) = Parser::new(source.chars()).load(&mut locations, false) {
+        let marker = e.marker();
+        return vec![Diagnostic::new(
+            marker.line(),
+            marker.col() + 1,
+            e.to_string(),
+        )];
+    }
+
+    let mut checker = Checker {
+        locations,
+        diagnostics: Vec::new(),
+    };
+
+    // first, the file has to parse at all. unknown keys are collected along the way
+    let mut unknown_keys = Vec::new();
+    let deserializer = serde_yaml::Deserializer::from_str(source);
+    let config: Config = match serde_ignored::deserialize(deserializer, |path| {
+        unknown_keys.push(path.to_string())
+    }) {
+        Ok(c) => c,
+        Err(e) => {
+            let (line, column) = match unknown_block_type_index(&e.to_string()) {
+                // serde reports an unknown type at the start of its block, not at the type
+                Some(i) if checker.locations.contains(&format!("blocks.{}.type", i)) => {
+                    checker.locations.find(&format!("blocks.{}.type", i))
+                }
+                _ => e
+                    .location()
+                    .map(|l| (l.line(), l.column()))
+                    .unwrap_or((1, 1)),
+            };
+            return vec![Diagnostic::new(line, column, e.to_string())];
+        }
+    };
+
+    for path in unknown_keys {
+        checker.unknown_key(&path);
+    }
+
+    // block options are flattened next to `id` and `type`, so serde can't tell us about unknown
+    // keys in them. they're checked one block at a time instead
+    if let Ok(value) = serde_yaml::from_str::<Value>(source) {
+        if let Some(blocks) = value.get("blocks").and_then(Value::as_sequence) {
+            let kinds = config.blocks.iter().flatten().map(|b| &b.kind);
+            for (i, (kind, block)) in kinds.zip(blocks).enumerate() {
+                checker.check_block_keys(i, kind, block);
+            }
+        }
+    }
+
+    checker.check_config(&config);
+
+    let mut diagnostics = checker.diagnostics;
+    diagnostics.sort();
+    diagnostics
+}
+
+/// Checks a parsed config, collecting diagnostics.
+struct Checker {
+    locations: Locations,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker {
+    /// Adds a diagnostic at the location of `path`.
+    fn report(&mut self, path: &str, message: String) {
+        let (line, column) = self.locations.find(path);
+        self.diagnostics
+            .push(Diagnostic::new(line, column, message));
+    }
+
+    /// Reports an unknown key. `path` may contain `?` segments from serde_ignored, which don't
+    /// exist in the file.
+    fn unknown_key(&mut self, path: &str) {
+        let path = path
+            .split('.')
+            .filter(|s| *s != "?")
+            .collect::<Vec<_>>()
+            .join(".");
+        let key = path.rsplit('.').next().unwrap_or_default().to_string();
+
+        self.report(&path, format!("unknown key `{}`", key));
+    }
+
+    /// Reports unknown keys in the block at `index` of the `blocks` list, which was parsed as
+    /// `kind`.
+    fn check_block_keys(&mut self, index: usize, kind: &BlockKind, block: &Value) {
+        let mut options = match block.as_mapping() {
+            Some(m) => m.clone(),
+            None => return,
+        };
+        options.remove(&Value::from("type"));
+        options.remove(&Value::from("id"));
+
+        // every kind is matched, so that a new kind can't go unchecked
+        let options = Value::Mapping(options);
+        let unknown = match kind {
+            BlockKind::Battery(_) => unknown_keys::<BatteryConfig>(options),
+            BlockKind::Brightness(_) => unknown_keys::<BrightnessConfig>(options),
+            BlockKind::Command(_) => unknown_keys::<CommandConfig>(options),
+            BlockKind::Disk(_) => unknown_keys::<DiskConfig>(options),
+            BlockKind::Feed(_) => unknown_keys::<FeedConfig>(options),
+            BlockKind::Keyboard(_) => unknown_keys::<KeyboardConfig>(options),
+            BlockKind::Network(_) => unknown_keys::<NetworkConfig>(options),
+            BlockKind::Privacy(_) => unknown_keys::<PrivacyConfig>(options),
+            BlockKind::Reminders(_) => unknown_keys::<RemindersConfig>(options),
+            BlockKind::Temperature(_) => unknown_keys::<TemperatureConfig>(options),
+            BlockKind::Timer(_) => unknown_keys::<TimerConfig>(options),
+            BlockKind::Utilization(_) => unknown_keys::<UtilizationConfig>(options),
+            BlockKind::Volume(_) => unknown_keys::<VolumeConfig>(options),
+            BlockKind::Watch(_) => unknown_keys::<WatchConfig>(options),
+            BlockKind::Weather(_) => unknown_keys::<WeatherConfig>(options),
+
+            // these blocks don't have any options
+            BlockKind::Date | BlockKind::Mpris => options
+                .as_mapping()
+                .map(|m| {
+                    m.iter()
+                        .filter_map(|(k, _)| k.as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        };
+
+        for path in unknown {
+            self.unknown_key(&format!("blocks.{}.{}", index, path));
+        }
+    }
+
+    fn check_config(&mut self, config: &Config) {
+        let block_configs = config.block_configs();
+
+        // ids have to be unique, and safe to put in shell commands
+        let mut ids = HashSet::new();
+        for (i, block) in config.blocks.iter().flatten().enumerate() {
+            let path = format!("blocks.{}.id", i);
+            if !is_valid_block_id(&block.id) {
+                self.report(&path, invalid_block_id_message(&block.id));
+            } else if !ids.insert(&block.id) {
+                self.report(
+                    &path,
+                    format!("more than one block has the id `{}`", block.id),
+                );
+            }
+        }
+
+        // every name in the order lists should be a block
+        let orders = [
+            ("primary_order", &config.primary_order),
+            ("secondary_order", &config.secondary_order),
+            ("tertiary_order", &config.tertiary_order),
+        ];
+        for (list, names) in orders {
+            // lists that aren't in the file are defaults, which may name blocks that aren't used
+            if !self.locations.contains(list) {
+                continue;
+            }
+
+            for (i, name) in names.iter().enumerate() {
+                if !block_configs.iter().any(|b| &b.id == name) {
+                    self.report(
+                        &format!("{}.{}", list, i),
+                        format!("`{}` in `{}` isn't the id of any block", name, list),
+                    );
+                }
+            }
+        }
+
+        // the theme should exist and parse, so that clients don't fail to start
+        if let Some(name) = &config.theme {
+            match theme::load(name) {
+                Ok(_) => (),
+                Err(MuseStatusError::Basic(e)) => self.report("theme", e.message),
+                Err(e) => self.report("theme", e.to_string()),
+            }
+        }
+
+        // styles should be for blocks that exist, and only replace icons that blocks have
+        for (id, style) in &config.styles {
+            let block = match block_configs.iter().find(|b| &b.id == id) {
+                Some(b) => b,
+                None => {
+                    self.report(
+                        &format!("styles.{}", id),
+                        format!("`{}` in `styles` isn't the id of any block", id),
+                    );
+                    continue;
+                }
+            };
+
+            if !style.icons.is_empty() && block.kind.icon_tables().is_empty() {
+                self.report(
+                    &format!("styles.{}.icons", id),
+                    format!("the icons of `{}` can't be replaced", id),
+                );
+            }
+        }
+
+        for (i, block) in block_configs.iter().enumerate() {
+            // if `blocks` isn't set, the options live in their older top-level places
+            let path = |option: &str| match (&config.blocks, &block.kind) {
+                (Some(_), _) => format!("blocks.{}.{}", i, option),
+                (None, BlockKind::Battery(_)) => format!("battery_config.{}", option),
+                (None, BlockKind::Weather(_)) => format!("weather_config.{}", option),
+                (None, BlockKind::Network(_)) => String::from("network_interface_name"),
+                (None, _) => option.to_string(),
+            };
+
+            match &block.kind {
+                BlockKind::Battery(c) => {
+                    self.check_battery_level(&path("warning_level"), &c.warning_level);
+                    self.check_battery_level(&path("alarm_level"), &c.alarm_level);
+                    self.check_device(
+                        &path("battery_id"),
+                        battery::SYS_POWER_SUPPLY_BASE_DIR,
+                        &c.battery_id,
+                        "battery",
+                    );
+                }
+                BlockKind::Brightness(c) => self.check_device(
+                    &path("brightness_id"),
+                    brightness::BASE_DIR,
+                    &c.brightness_id,
+                    "backlight",
+                ),
+                BlockKind::Network(c) => self.check_device(
+                    &path("interface_name"),
+                    network::SYS_NET_DIR,
+                    &c.interface_name,
+                    "network interface",
+                ),
+                BlockKind::Weather(c) => {
+                    if c.openweathermap_key.is_empty() {
+                        self.report(
+                            &path("openweathermap_key"),
+                            format!("weather block `{}` needs an `openweathermap_key`", block.id),
+                        );
+                    }
+                    if c.ipstack_key.is_empty() {
+                        self.report(
+                            &path("ipstack_key"),
+                            format!("weather block `{}` needs an `ipstack_key`", block.id),
+                        );
+                    }
+                }
+                BlockKind::Temperature(c) => {
+                    if let Some(sensor) = &c.sensor {
+                        let sensors = temperature::read_sensors(Path::new(&c.sys_root));
+                        if !sensors.iter().any(|s| s.is_called(sensor)) {
+                            self.report(
+                                &path("sensor"),
+                                format!("there's no temperature sensor called `{}`", sensor),
+                            );
+                        }
+                    }
+                }
+                BlockKind::Utilization(c) => {
+                    let thresholds = [
+                        ("cpu_warning", c.cpu_warning),
+                        ("cpu_alarm", c.cpu_alarm),
+                        ("memory_warning", c.memory_warning),
+                        ("memory_alarm", c.memory_alarm),
+                    ];
+                    for (option, value) in thresholds {
+                        self.check_fraction(&path(option), value);
+                    }
+                }
+                BlockKind::Disk(c) => {
+                    self.check_disk_level(&path("warning_level"), &c.warning_level);
+                    self.check_disk_level(&path("alarm_level"), &c.alarm_level);
+
+                    for (j, mount) in c.mounts.iter().enumerate() {
+                        let mount_path = path(&format!("mounts.{}", j));
+                        if let Some(level) = &mount.warning_level {
+                            self.check_disk_level(&format!("{}.warning_level", mount_path), level);
+                        }
+                        if let Some(level) = &mount.alarm_level {
+                            self.check_disk_level(&format!("{}.alarm_level", mount_path), level);
+                        }
+                        if !Path::new(&mount.path).exists() {
+                            self.report(
+                                &format!("{}.path", mount_path),
+                                format!("there's nothing at {}", mount.path),
+                            );
+                        }
+                    }
+                }
+                BlockKind::Command(c) => {
+                    if c.command.trim().is_empty() {
+                        self.report(
+                            &path("command"),
+                            format!("command block `{}` needs a `command`", block.id),
+                        );
+                    }
+                    if c.mode == CommandMode::Interval && c.timeout_seconds == 0 {
+                        self.report(
+                            &path("timeout_seconds"),
+                            String::from(
+                                "a timeout of 0 seconds doesn't give the command any time to run",
+                            ),
+                        );
+                    }
+                }
+                BlockKind::Feed(c) => {
+                    if c.path.is_empty() {
+                        self.report(
+                            &path("path"),
+                            format!("feed block `{}` needs a `path`", block.id),
+                        );
+                    } else if !Path::new(&c.path).parent().is_some_and(Path::is_dir) {
+                        self.report(
+                            &path("path"),
+                            format!("the directory of {} doesn't exist", c.path),
+                        );
+                    }
+                }
+                BlockKind::Keyboard(c) => {
+                    if !keyboard::has_caps_lock_led(Path::new(&c.sys_root)) {
+                        self.report(
+                            &path("sys_root"),
+                            format!("no caps lock LEDs found in {}/class/leds", c.sys_root),
+                        );
+                    }
+                }
+                BlockKind::Reminders(c) => {
+                    if c.alarm_minutes > c.warning_minutes {
+                        self.report(
+                            &path("alarm_minutes"),
+                            String::from("`alarm_minutes` is more than `warning_minutes`, so the block will never show a warning"),
+                        );
+                    }
+
+                    // a missing schedule file isn't a problem here; the block reports it
+                    let file = reminders::schedule_path(c).ok();
+                    if let Some(source) = file.and_then(|f| fs::read_to_string(f).ok()) {
+                        let (_, errors) = reminders::parse_schedule(&source);
+                        for (line, message) in errors {
+                            self.report(
+                                &path("file"),
+                                format!("line {} of the schedule file: {}", line, message),
+                            );
+                        }
+                    }
+                }
+                BlockKind::Watch(c) => {
+                    for (j, rule) in c.rules.iter().enumerate() {
+                        let rule_path = path(&format!("rules.{}", j));
+                        if rule.name.is_none() && rule.regex.is_none() {
+                            self.report(
+                                &rule_path,
+                                format!("watch rule `{}` needs a `name` or a `regex`", rule.label),
+                            );
+                        }
+                        if let Some(Err(e)) = rule.regex.as_deref().map(Regex::new) {
+                            self.report(
+                                &format!("{}.regex", rule_path),
+                                format!("invalid regex: {}", regex_error_message(&e)),
+                            );
+                        }
+                    }
+                }
+                BlockKind::Date
+                | BlockKind::Mpris
+                | BlockKind::Privacy(_)
+                | BlockKind::Timer(_)
+                | BlockKind::Volume(_) => (),
+            }
+        }
+    }
+
+    fn check_battery_level(&mut self, path: &str, level: &BatteryLevel) {
+        match level {
+            BatteryLevel::Percentage(p) => self.check_fraction(path, *p),
+            BatteryLevel::MinutesLeft(m) if *m < 0 => self.report(
+                path,
+                format!("minutes left can't be negative, but is {}", m),
+            ),
+            _ => (),
+        }
+    }
+
+    fn check_disk_level(&mut self, path: &str, level: &DiskLevel) {
+        match level {
+            DiskLevel::PercentageFree(p) => self.check_fraction(path, *p),
+            DiskLevel::GibFree(g) if *g < 0.0 => {
+                self.report(path, format!("free space can't be negative, but is {}", g))
+            }
+            _ => (),
+        }
+    }
+
+    fn check_fraction(&mut self, path: &str, value: f32) {
+        if !(0.0..=1.0).contains(&value) {
+            self.report(
+                path,
+                format!(
+                    "percentages are written from 0.0 to 1.0 (like 0.15 for 15%), but this one is {}",
+                    value
+                ),
+            );
+        }
+    }
+
+    fn check_device(&mut self, path: &str, dir: &str, name: &str, kind: &str) {
+        let device = Path::new(dir).join(name);
+        if !device.exists() {
+            self.report(path, format!("there's no {} at {}", kind, device.display()));
+        }
+    }
+}
+
+/// Returns the last line of a regex error, which says what's wrong. The lines before it point
+/// at the mistake, which doesn't fit in a diagnostic.
+fn regex_error_message(e: &regex::Error) -> String {
+    let message = e.to_string();
+    let last_line = message.lines().last().unwrap_or_default();
+    last_line.trim_start_matches("error: ").to_string()
+}
+
+/// Returns the index of the block with an unknown type, if that's what the serde error `message`
+/// is about.
+fn unknown_block_type_index(message: &str) -> Option<usize> {
+    let re = Regex::new(r"^blocks\[(\d+)\]: unknown variant").unwrap();
+    re.captures(message)?.get(1)?.as_str().parse().ok()
+}
+
+/// Returns the paths of any keys in `value` that `T` doesn't know about.
+fn unknown_keys<T: DeserializeOwned>(value: Value) -> Vec<String> {
+    let mut unknown = Vec::new();
+    let _ = serde_ignored::deserialize::<_, _, T>(value, |path| unknown.push(path.to_string()));
+    unknown
+}
+
+/// The line and column of every key and sequence item in a YAML document, by dotted path (like
+/// `blocks.2.battery_id`).
+#[derive(Default)]
+struct Locations {
+    positions: HashMap<String, (usize, usize)>,
+    stack: Vec<Frame>,
+}
+
+/// A mapping or sequence that's being read.
+enum Frame {
+    /// A mapping. If `key` is set, the next node is its value; otherwise it's a key.
+    Mapping { path: String, key: Option<String> },
+
+    /// A sequence, with the index of the next item.
+    Sequence { path: String, index: usize },
+}
+
+impl Locations {
+    /// Returns the location of `path`. If `path` itself wasn't found, the location of its
+    /// closest parent is used, and if there is none, the start of the file.
+    fn find(&self, path: &str) -> (usize, usize) {
+        let mut path = path;
+        loop {
+            if let Some(p) = self.positions.get(path) {
+                return *p;
+            }
+
+            match path.rfind('.') {
+                Some(i) => path = &path[..i],
+                None => return (1, 1),
+            }
+        }
+    }
+
+    /// Returns true if `path` is in the document.
+    fn contains(&self, path: &str) -> bool {
+        self.positions.contains_key(path)
+    }
+
+    /// Returns the path of the node that starts now, recording its location if it's a sequence
+    /// item. Returns `None` if the node is a mapping key.
+    fn next_node(&mut self, event: &Event, mark: Marker) -> Option<String> {
+        let position = (mark.line(), mark.col() + 1);
+        match self.stack.last_mut() {
+            None => Some(String::new()),
+            Some(Frame::Sequence { path, index }) => {
+                let item_path = join(path, &index.to_string());
+                *index += 1;
+                self.positions.insert(item_path.clone(), position);
+                Some(item_path)
+            }
+            Some(Frame::Mapping { path, key }) => match key.take() {
+                Some(k) => Some(join(path, &k)),
+                None => {
+                    // keys that aren't scalars can't be written as paths, but still need a
+                    // placeholder so that the value after them is read as a value
+                    let k = match event {
+                        Event::Scalar(s, ..) => s.clone(),
+                        _ => String::from("?"),
+                    };
+                    self.positions.insert(join(path, &k), position);
+                    *key = Some(k);
+                    None
+                }
+            },
+        }
+    }
+}
+
+impl MarkedEventReceiver for Locations {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        match event {
+            Event::Scalar(..) | Event::Alias(_) => {
+                self.next_node(&event, mark);
+            }
+            Event::MappingStart(_) => {
+                let path = self.next_node(&event, mark).unwrap_or_default();
+                self.stack.push(Frame::Mapping { path, key: None });
+            }
+            Event::SequenceStart(_) => {
+                let path = self.next_node(&event, mark).unwrap_or_default();
+                self.stack.push(Frame::Sequence { path, index: 0 });
+            }
+            Event::MappingEnd | Event::SequenceEnd => {
+                self.stack.pop();
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Joins two parts of a dotted path.
+fn join(parent: &str, child: &str) -> String {
+    if parent.is_empty() {
+        child.to_string()
+    } else {
+        format!("{}.{}", parent, child)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the line and column of every problem found in `source`.
+    fn positions(source: &str) -> Vec<(usize, usize)> {
+        check_str(source)
+            .into_iter()
+            .map(|d| (d.line, d.column))
+            .collect()
+    }
+
+    #[test]
+    fn finds_unknown_keys() {
+        let source = "\
+blocks:
+  - id: disk
+    type: disk
+    mountz: []
+  - id: date
+    type: date
+    format: '%H:%M'
+primary_order: [disk, date]
+";
+        let diagnostics = check_str(source);
+
+        assert_eq!(positions(source), vec![(4, 5), (7, 5)]);
+        assert_eq!(diagnostics[0].message, "unknown key `mountz`");
+        assert_eq!(diagnostics[1].message, "unknown key `format`");
+    }
+
+    #[test]
+    fn finds_unknown_types_at_their_type() {
+        let source = "\
+blocks:
+  - id: date
+    type: date
+  - id: teleporter
+    type: teleporter
+";
+        let diagnostics = check_str(source);
+
+        assert_eq!(positions(source), vec![(5, 5)]);
+        assert!(diagnostics[0]
+            .message
+            .contains("unknown variant `teleporter`"));
+    }
+
+    #[test]
+    fn finds_bad_and_duplicate_ids() {
+        let source = "\
+blocks:
+  - id: date
+    type: date
+  - id: 'no spaces'
+    type: mpris
+  - id: date
+    type: disk
+";
+        let diagnostics = check_str(source);
+
+        assert_eq!(positions(source), vec![(4, 5), (6, 5)]);
+        assert!(diagnostics[0].message.contains("isn't a valid block id"));
+        assert!(diagnostics[1].message.contains("more than one block"));
+    }
+}
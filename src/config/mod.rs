@@ -0,0 +1,958 @@
+/// A module for checking config files for mistakes.
+pub mod check;
+
+use crate::{
+    battery::{self, BatteryBlock, BatteryLevel},
+    brightness::{self, BrightnessBlock},
+    command::{CommandBlock, CommandFormat, CommandMode},
+    date::{self, DateBlock},
+    disk::{DiskBlock, DiskLevel},
+    errors::BasicError,
+    errors::MuseStatusError,
+    feed::FeedBlock,
+    format::{
+        blocks::{click::MouseButton, Block},
+        color::{self, RGBA},
+        style::{self, Align, BlockStyle, MinWidth},
+        Attention, Banner,
+    },
+    keyboard::{self, KeyboardBlock},
+    mpris::MprisBlock,
+    network::NetworkBlock,
+    privacy::PrivacyBlock,
+    process_watch::ProcessWatchBlock,
+    reminders::RemindersBlock,
+    temperature::{self, TemperatureBlock},
+    timer::TimerBlock,
+    transport::DaemonAddr,
+    utilization::{self, UtilizationBlock},
+    volume::{self, VolumeBlock},
+    weather::{Units, WeatherBlock},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    path::Path,
+    path::PathBuf,
+};
+
+/// Configuration for all of muse-status.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    /// The address to run and listen on. By default, this is a Unix socket in the user's runtime
+    /// directory. Use `tcp:<host>:<port>` to listen on TCP instead.
+    pub daemon_addr: DaemonAddr,
+
+    /// The blocks the daemon runs. Blocks that aren't listed aren't started. If this isn't set,
+    /// one of each block is run, configured by the older top-level options below.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocks: Option<Vec<BlockConfig>>,
+
+    /// The ordering of primary-level blocks, by block id.
+    pub primary_order: Vec<String>,
+
+    /// The ordering of secondary-level blocks, by block id.
+    pub secondary_order: Vec<String>,
+
+    /// The ordering of tertiary-level blocks, by block id.
+    pub tertiary_order: Vec<String>,
+
+    /// The name of the brightness directory in Linux's /sys/class/backlight directory. Only used
+    /// if `blocks` isn't set.
+    #[serde(skip_serializing)]
+    pub brightness_id: String,
+
+    /// The audio sink to use for the volume block. Only used if `blocks` isn't set.
+    #[serde(skip_serializing)]
+    pub volume_sink: Option<String>,
+
+    /// The name of the user's network interface (like `wlan0`). Only used if `blocks` isn't set.
+    #[serde(skip_serializing)]
+    pub network_interface_name: String,
+
+    /// Battery config to use for the battery block. Only used if `blocks` isn't set.
+    #[serde(skip_serializing)]
+    pub battery_config: BatteryConfig,
+
+    /// Weather config to use for the weather block. Only used if `blocks` isn't set.
+    #[serde(skip_serializing)]
+    pub weather_config: WeatherConfig,
+
+    /// Actions to run when blocks are clicked, by block name and then by button. A binding
+    /// replaces the block's own reaction to that button.
+    pub click_bindings: HashMap<String, HashMap<MouseButton, ClickAction>>,
+
+    /// The theme clients draw with, by name, unless they're given `--theme`. If this isn't set,
+    /// the default theme is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+
+    /// How clients draw blocks, by block id, on top of the theme.
+    pub styles: HashMap<String, StyleConfig>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        // the default config lists its blocks, so that new config files use `blocks`. configs
+        // without `blocks` still fall back to the older options, since `blocks` has its own
+        // serde default of `None`
+        let blocks = vec![
+            BlockConfig::new("date", BlockKind::Date),
+            BlockConfig::new("weather", BlockKind::Weather(Default::default())),
+            BlockConfig::new("mpris", BlockKind::Mpris),
+            BlockConfig::new("brightness", BlockKind::Brightness(Default::default())),
+            BlockConfig::new("volume", BlockKind::Volume(Default::default())),
+            BlockConfig::new("network", BlockKind::Network(Default::default())),
+            BlockConfig::new("battery", BlockKind::Battery(Default::default())),
+        ];
+
+        Self {
+            daemon_addr: DaemonAddr::default(),
+            blocks: Some(blocks),
+            primary_order: vec![
+                "date".to_string(),
+                "weather".to_string(),
+                "mpris".to_string(),
+            ],
+            secondary_order: vec![
+                "brightness".to_string(),
+                "volume".to_string(),
+                "network".to_string(),
+                "battery".to_string(),
+            ],
+            tertiary_order: vec![],
+
+            brightness_id: String::from("amdgpu_bl0"),
+            network_interface_name: String::from("wlan0"),
+            volume_sink: None,
+
+            battery_config: Default::default(),
+            weather_config: Default::default(),
+
+            click_bindings: HashMap::new(),
+            theme: None,
+            styles: HashMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Parses the configuration file at the path. Returns an error if the file doesn't exist.
+    pub fn from_file<P: AsRef<Path>>(p: P) -> Result<Config, MuseStatusError> {
+        let path = p.as_ref();
+        let file = File::open(path).map_err(|e| {
+            MuseStatusError::Basic(BasicError {
+                message: format!("couldn't open {}: {}", path.display(), e),
+            })
+        })?;
+
+        let config: Self = serde_yaml::from_reader(file).map_err(|e| {
+            MuseStatusError::Basic(BasicError {
+                message: format!("couldn't parse the configuration file: {}", e),
+            })
+        })?;
+
+        config.check_block_ids()?;
+
+        Ok(config)
+    }
+
+    /// Like `from_file`, but if the file doesn't exist, the default config is written to it
+    /// first. Only use this for the default path, so that a mistyped path isn't silently filled
+    /// with defaults.
+    pub fn from_file_or_create<P: AsRef<Path>>(p: P) -> Result<Config, MuseStatusError> {
+        let path = p.as_ref();
+        if !path.exists() {
+            // if the file path doesn't exist, write the default config to it, then return the
+            // default config.
+            Self::write_default_config(path)?;
+            Ok(Self::default())
+        } else {
+            // if the path already exists, read and parse
+            Self::from_file(path)
+        }
+    }
+
+    fn write_default_config(path: &Path) -> Result<(), MuseStatusError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        Ok(std::fs::write(
+            path,
+            serde_yaml::to_string(&Self::default()).map_err(|e| {
+                MuseStatusError::Basic(BasicError {
+                    message: format!("{}", e),
+                })
+            })?,
+        )?)
+    }
+
+    /// Returns the configs of the blocks the daemon should run. If `blocks` isn't set, one of
+    /// each block is returned, configured by the older top-level options.
+    pub fn block_configs(&self) -> Vec<BlockConfig> {
+        if let Some(blocks) = &self.blocks {
+            return blocks.clone();
+        }
+
+        vec![
+            BlockConfig::new("date", BlockKind::Date),
+            BlockConfig::new("weather", BlockKind::Weather(self.weather_config.clone())),
+            BlockConfig::new("mpris", BlockKind::Mpris),
+            BlockConfig::new(
+                "brightness",
+                BlockKind::Brightness(BrightnessConfig {
+                    brightness_id: self.brightness_id.clone(),
+                    ..Default::default()
+                }),
+            ),
+            BlockConfig::new(
+                "volume",
+                BlockKind::Volume(VolumeConfig {
+                    sink: self.volume_sink.clone(),
+                    ..Default::default()
+                }),
+            ),
+            BlockConfig::new(
+                "network",
+                BlockKind::Network(NetworkConfig {
+                    interface_name: self.network_interface_name.clone(),
+                }),
+            ),
+            BlockConfig::new("battery", BlockKind::Battery(self.battery_config.clone())),
+        ]
+    }
+
+    /// Returns the styles clients draw blocks with, by block id. Only blocks that have a style are
+    /// included.
+    pub fn block_styles(&self) -> HashMap<String, BlockStyle> {
+        self.block_configs()
+            .into_iter()
+            .filter_map(|block| {
+                let config = self.styles.get(&block.id)?;
+                let style = BlockStyle {
+                    icon_color: config.icon_color,
+                    icons: style::replace_icons(&block.kind.icon_tables(), &config.icons),
+                    min_width: config.min_width.clone(),
+                    align: config.align,
+                    separator: config.separator,
+                };
+
+                Some((block.id, style))
+            })
+            .collect()
+    }
+
+    /// Returns an error if two blocks share the same id, or if an id isn't valid.
+    fn check_block_ids(&self) -> Result<(), MuseStatusError> {
+        let mut ids = HashSet::new();
+        for block in self.blocks.iter().flatten() {
+            if !is_valid_block_id(&block.id) {
+                return Err(MuseStatusError::Basic(BasicError {
+                    message: invalid_block_id_message(&block.id),
+                }));
+            }
+
+            if !ids.insert(&block.id) {
+                return Err(MuseStatusError::Basic(BasicError {
+                    message: format!("more than one block has the id `{}`", block.id),
+                }));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns true if `id` can be used as a block id. Ids are limited to letters, digits, `-` and
+/// `_`, because they end up in places like the shell commands lemonbar runs for clicks.
+pub fn is_valid_block_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Returns the message for an id that `is_valid_block_id` refuses.
+fn invalid_block_id_message(id: &str) -> String {
+    format!(
+        "`{}` isn't a valid block id; ids can only have letters, digits, `-` and `_`",
+        id
+    )
+}
+
+/// Something the daemon does when a block is clicked.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClickAction {
+    /// Runs a shell command with `sh -c`. The name of the clicked block and the button used are
+    /// available to the command as `$MUSE_BLOCK` and `$MUSE_BUTTON`.
+    Command(String),
+
+    /// Updates blocks by name, like `muse-status notify` does. If no names are given, the clicked
+    /// block is updated.
+    Update(Vec<String>),
+
+    /// Shows a banner on every subscribed client.
+    Banner(Banner),
+}
+
+/// How clients draw a block, on top of the theme. Most of these are only for status bars that use
+/// the JSON protocol, like i3bar and swaybar; icons are replaced everywhere.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct StyleConfig {
+    /// The color of the block's icon, like `#rrggbb`. If not set, the icon is the color of the
+    /// block's text. Dim, warning and alarm outputs always color their icons like their text.
+    #[serde(
+        deserialize_with = "color::deserialize_optional_color",
+        serialize_with = "color::serialize_optional_color",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub icon_color: Option<RGBA>,
+
+    /// Icons to draw in place of the block's built-in ones, in the same order: from lowest to
+    /// highest for battery, brightness and volume blocks, and from 12 o'clock on for date blocks.
+    /// Lists of a different length are spread over the built-in ones.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub icons: Vec<String>,
+
+    /// The minimum width of the block, in pixels or as text whose width the block should fit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_width: Option<MinWidth>,
+
+    /// Where the block's text is put, if `min_width` makes the block wider than it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub align: Option<Align>,
+
+    /// Whether a separator is drawn after the block.
+    pub separator: bool,
+}
+
+impl Default for StyleConfig {
+    fn default() -> Self {
+        Self {
+            icon_color: None,
+            icons: Vec::new(),
+            min_width: None,
+            align: None,
+            separator: true,
+        }
+    }
+}
+
+/// Configuration for one block instance.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct BlockConfig {
+    /// A unique id for the block. This is the name used in `primary_order` and the other order
+    /// lists, in click bindings, and on the command line.
+    pub id: String,
+
+    /// The type of the block, with its type-specific options.
+    #[serde(flatten)]
+    pub kind: BlockKind,
+}
+
+impl BlockConfig {
+    /// Creates a new `BlockConfig`.
+    pub fn new(id: &str, kind: BlockKind) -> Self {
+        Self {
+            id: id.to_string(),
+            kind,
+        }
+    }
+
+    /// Creates the block described by this config.
+    pub fn to_block(&self) -> Result<Box<dyn Block>, MuseStatusError> {
+        let block: Box<dyn Block> = match &self.kind {
+            BlockKind::Battery(c) => Box::new(BatteryBlock::new(c.clone()).with_id(&self.id)),
+            BlockKind::Brightness(c) => Box::new(
+                BrightnessBlock::new(&c.brightness_id)
+                    .with_slider(c.slider)
+                    .with_id(&self.id),
+            ),
+            BlockKind::Command(c) => Box::new(CommandBlock::new(c.clone()).with_id(&self.id)),
+            BlockKind::Date => Box::new(DateBlock::new().with_id(&self.id)),
+            BlockKind::Disk(c) => Box::new(DiskBlock::new(c.clone()).with_id(&self.id)),
+            BlockKind::Feed(c) => Box::new(FeedBlock::new(c.clone()).with_id(&self.id)),
+            BlockKind::Keyboard(c) => Box::new(KeyboardBlock::new(c.clone()).with_id(&self.id)),
+            BlockKind::Mpris => Box::new(MprisBlock::new().with_id(&self.id)),
+            BlockKind::Network(c) => {
+                Box::new(NetworkBlock::new(&c.interface_name)?.with_id(&self.id))
+            }
+            BlockKind::Volume(c) => {
+                let block = match &c.sink {
+                    Some(sink) => VolumeBlock::new(sink),
+                    None => VolumeBlock::default(),
+                };
+
+                Box::new(block.with_slider(c.slider).with_id(&self.id))
+            }
+            BlockKind::Privacy(c) => Box::new(PrivacyBlock::new(c.clone()).with_id(&self.id)),
+            BlockKind::Reminders(c) => Box::new(RemindersBlock::new(c.clone()).with_id(&self.id)),
+            BlockKind::Temperature(c) => {
+                Box::new(TemperatureBlock::new(c.clone()).with_id(&self.id))
+            }
+            BlockKind::Timer(c) => Box::new(TimerBlock::new(c.clone()).with_id(&self.id)),
+            BlockKind::Utilization(c) => {
+                Box::new(UtilizationBlock::new(c.clone()).with_id(&self.id))
+            }
+            BlockKind::Watch(c) => Box::new(ProcessWatchBlock::new(c.clone())?.with_id(&self.id)),
+            BlockKind::Weather(c) => Box::new(WeatherBlock::new(c.clone()).with_id(&self.id)),
+        };
+
+        Ok(block)
+    }
+}
+
+/// The type of a block, with its type-specific options.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BlockKind {
+    /// A battery block.
+    Battery(BatteryConfig),
+
+    /// A backlight brightness block.
+    Brightness(BrightnessConfig),
+
+    /// A block that shows what a command prints.
+    Command(CommandConfig),
+
+    /// A date and time block.
+    Date,
+
+    /// A disk space block.
+    Disk(DiskConfig),
+
+    /// A block whose content is pushed by other programs, through a named pipe or a file.
+    Feed(FeedConfig),
+
+    /// A lock keys block.
+    Keyboard(KeyboardConfig),
+
+    /// A media player block.
+    Mpris,
+
+    /// A network interface block.
+    Network(NetworkConfig),
+
+    /// A camera and microphone use block.
+    Privacy(PrivacyConfig),
+
+    /// A block of upcoming reminders, read from a schedule file.
+    Reminders(RemindersConfig),
+
+    /// A temperature sensor block.
+    Temperature(TemperatureConfig),
+
+    /// A block of countdowns and stopwatches, controlled with `muse-status timer`.
+    Timer(TimerConfig),
+
+    /// A CPU, memory and swap usage block.
+    Utilization(UtilizationConfig),
+
+    /// An audio volume block.
+    Volume(VolumeConfig),
+
+    /// A block that shows when certain processes are running.
+    Watch(WatchConfig),
+
+    /// A weather block.
+    Weather(WeatherConfig),
+}
+
+impl BlockKind {
+    /// Returns the built-in icon tables of the block, which `StyleConfig::icons` replaces. Blocks
+    /// that don't pick their icons from a table have none.
+    pub fn icon_tables(&self) -> Vec<&'static [char]> {
+        match self {
+            Self::Battery(_) => vec![&battery::DISCHARGING_ICONS, &battery::CHARGING_ICONS],
+            Self::Brightness(_) => vec![&brightness::BRIGHTNESS_ICONS],
+            Self::Date => vec![&date::CLOCK_ICONS],
+            Self::Volume(_) => vec![&volume::VOLUME_ICONS],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Configuration for a brightness block.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct BrightnessConfig {
+    /// The name of the brightness directory in Linux's /sys/class/backlight directory.
+    pub brightness_id: String,
+
+    /// Shows the brightness as a slider instead of a percentage.
+    pub slider: bool,
+}
+
+impl Default for BrightnessConfig {
+    fn default() -> Self {
+        Self {
+            brightness_id: String::from("amdgpu_bl0"),
+            slider: false,
+        }
+    }
+}
+
+/// Configuration for a feed block.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct FeedConfig {
+    /// The file or named pipe that payloads are written to.
+    pub path: String,
+
+    /// If true, `path` is a named pipe, which is created if it doesn't exist, and every line
+    /// written to it is a payload. Otherwise, `path` is a file whose content is the payload.
+    pub fifo: bool,
+
+    /// How long a payload is shown after it's written, in seconds, unless it has its own
+    /// `expire_seconds`. If not set, payloads are shown until they're replaced.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expire_seconds: Option<u32>,
+}
+
+/// Configuration for a keyboard (lock keys) block.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct KeyboardConfig {
+    /// How often to update, in seconds. If 0, the block only updates when `muse-status notify`
+    /// is sent for it, which is enough if caps lock is bound to send it.
+    pub update_interval_seconds: u32,
+
+    /// The directory to look for LEDs in, under `class/leds`. Only worth changing to read from
+    /// somewhere other than /sys.
+    pub sys_root: String,
+}
+
+impl Default for KeyboardConfig {
+    fn default() -> Self {
+        Self {
+            update_interval_seconds: 1,
+            sys_root: String::from(keyboard::SYS_DIR),
+        }
+    }
+}
+
+/// Configuration for a network block.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// The name of the network interface (like `wlan0`).
+    pub interface_name: String,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            interface_name: String::from("wlan0"),
+        }
+    }
+}
+
+/// Configuration for a volume block.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct VolumeConfig {
+    /// The audio sink to use. If not set, the default sink is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sink: Option<String>,
+
+    /// Shows the volume as a slider instead of a percentage.
+    pub slider: bool,
+}
+
+/// Configuration for a command block.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct CommandConfig {
+    /// The command to run, with `sh -c`.
+    pub command: String,
+
+    /// Whether the command is run every interval or keeps running.
+    pub mode: CommandMode,
+
+    /// Whether the command prints plain text or JSON.
+    pub format: CommandFormat,
+
+    /// The icon shown, unless the command prints one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<char>,
+
+    /// How often to run the command, in seconds. In persistent mode, this is how long to wait
+    /// before restarting the command if it exits.
+    pub interval_seconds: u32,
+
+    /// How long the command can take before it's killed, in seconds. Only used in interval mode.
+    pub timeout_seconds: u32,
+}
+
+impl Default for CommandConfig {
+    fn default() -> Self {
+        Self {
+            command: String::new(),
+            mode: CommandMode::Interval,
+            format: CommandFormat::Text,
+            icon: None,
+            interval_seconds: 10,
+            timeout_seconds: 5,
+        }
+    }
+}
+
+/// Configuration for a disk block.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct DiskConfig {
+    /// The mounts to show.
+    pub mounts: Vec<MountConfig>,
+
+    /// The free space at which a mount shows a warning, unless the mount sets its own.
+    pub warning_level: DiskLevel,
+
+    /// The free space at which a mount shows an alarm, unless the mount sets its own.
+    pub alarm_level: DiskLevel,
+
+    /// Whether to hide mounts until they reach their warning level. If no mounts are left, the
+    /// block is hidden.
+    pub hide_until_warning: bool,
+
+    /// How often to update, in seconds.
+    pub update_interval_seconds: u32,
+}
+
+impl Default for DiskConfig {
+    fn default() -> Self {
+        Self {
+            mounts: vec![MountConfig {
+                path: String::from("/"),
+                label: None,
+                warning_level: None,
+                alarm_level: None,
+            }],
+            warning_level: DiskLevel::PercentageFree(0.15),
+            alarm_level: DiskLevel::PercentageFree(0.05),
+            hide_until_warning: false,
+            update_interval_seconds: 30,
+        }
+    }
+}
+
+/// A mount shown by a disk block.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct MountConfig {
+    /// The path the filesystem is mounted at (or any path inside it).
+    pub path: String,
+
+    /// The label shown next to the free space. If not set, the path is shown.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+
+    /// The free space at which this mount shows a warning.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning_level: Option<DiskLevel>,
+
+    /// The free space at which this mount shows an alarm.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alarm_level: Option<DiskLevel>,
+}
+
+/// Configuration for a privacy block.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct PrivacyConfig {
+    /// Device files that count as cameras when a process has them open. A pattern ending in `*`
+    /// matches any file starting with the rest of it. If empty, cameras aren't watched.
+    pub cameras: Vec<String>,
+
+    /// The ids of the sound cards (as in /proc/asound/card*/id) whose microphones are watched.
+    /// Patterns work like they do for `cameras`. If empty, microphones aren't watched.
+    pub microphones: Vec<String>,
+
+    /// How often to update, in seconds.
+    pub update_interval_seconds: u32,
+
+    /// The directory to look for processes and sound cards in. Only worth changing to read from
+    /// somewhere other than /proc.
+    pub proc_root: String,
+}
+
+impl Default for PrivacyConfig {
+    fn default() -> Self {
+        Self {
+            cameras: vec![String::from("/dev/video*")],
+            microphones: vec![String::from("*")],
+            update_interval_seconds: 2,
+            proc_root: String::from(utilization::PROC_DIR),
+        }
+    }
+}
+
+/// Configuration for a process watch block.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct WatchConfig {
+    /// The rules to match processes with. The block is hidden while no rule matches.
+    pub rules: Vec<WatchRule>,
+
+    /// How often to update, in seconds.
+    pub update_interval_seconds: u32,
+
+    /// The directory to look for processes in. Only worth changing to read from somewhere other
+    /// than /proc.
+    pub proc_root: String,
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            update_interval_seconds: 2,
+            proc_root: String::from(utilization::PROC_DIR),
+        }
+    }
+}
+
+/// A rule for a process watch block. A process matches if it matches both `name` and `regex`,
+/// whichever are set. At least one must be.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct WatchRule {
+    /// The text shown while the rule matches.
+    pub label: String,
+
+    /// The exact name of the process, as in /proc/<pid>/comm (like `rsync`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// A regular expression to search the command line of the process for. Arguments are
+    /// separated by spaces (like `cargo build`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regex: Option<String>,
+
+    /// The icon shown while the rule matches.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<char>,
+
+    /// The attention level of the block while the rule matches.
+    #[serde(default)]
+    pub attention: Attention,
+}
+
+/// Configuration for a temperature block. Levels are in degrees Celsius.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct TemperatureConfig {
+    /// The label of the sensor to show (like `Package id 0` or `Tctl`), or the name of its
+    /// device. If not set, the hottest sensor is shown.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sensor: Option<String>,
+
+    /// The temperature at which the block shows a warning.
+    pub warning_level: f32,
+
+    /// The temperature at which the block shows an alarm.
+    pub alarm_level: f32,
+
+    /// How often to update, in seconds.
+    pub update_interval_seconds: u32,
+
+    /// The directory to look for hwmon devices and thermal zones in, under `class/`. Only worth
+    /// changing to read from somewhere other than /sys.
+    pub sys_root: String,
+}
+
+impl Default for TemperatureConfig {
+    fn default() -> Self {
+        Self {
+            sensor: None,
+            warning_level: 80.0,
+            alarm_level: 95.0,
+            update_interval_seconds: 5,
+            sys_root: String::from(temperature::SYS_DIR),
+        }
+    }
+}
+
+/// Configuration for a reminders block. Times are in minutes before a reminder goes off.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct RemindersConfig {
+    /// The schedule file to read reminders from. If not set, a file named `reminders` is read
+    /// from muse-status's config directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+
+    /// How far ahead reminders are shown.
+    pub window_minutes: u32,
+
+    /// When the block shows a warning.
+    pub warning_minutes: u32,
+
+    /// When the block shows an alarm.
+    pub alarm_minutes: u32,
+}
+
+impl Default for RemindersConfig {
+    fn default() -> Self {
+        Self {
+            file: None,
+            window_minutes: 60,
+            warning_minutes: 10,
+            alarm_minutes: 2,
+        }
+    }
+}
+
+/// Configuration for a timer block.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct TimerConfig {
+    /// The file timers are saved to, so that they survive the daemon restarting. If not set,
+    /// timers are saved in muse-status's data directory, in a file named after the block.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state_file: Option<String>,
+}
+
+/// Configuration for a utilization block. Thresholds are written from 0.0 to 1.0, like battery
+/// percentages.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct UtilizationConfig {
+    /// The CPU usage at which the block shows a warning.
+    pub cpu_warning: f32,
+
+    /// The CPU usage at which the block shows an alarm.
+    pub cpu_alarm: f32,
+
+    /// The memory usage at which the block shows a warning.
+    pub memory_warning: f32,
+
+    /// The memory usage at which the block shows an alarm.
+    pub memory_alarm: f32,
+
+    /// Whether to show the usage of each core instead of all of them together.
+    pub per_core: bool,
+
+    /// How often to update, in seconds.
+    pub update_interval_seconds: u32,
+
+    /// The directory to read `stat` and `meminfo` from. Only worth changing to read from
+    /// somewhere other than /proc, like a container's or a copy of it.
+    pub proc_root: String,
+}
+
+impl Default for UtilizationConfig {
+    fn default() -> Self {
+        Self {
+            cpu_warning: 0.75,
+            cpu_alarm: 0.95,
+            memory_warning: 0.80,
+            memory_alarm: 0.95,
+            per_core: false,
+            update_interval_seconds: 2,
+            proc_root: String::from(utilization::PROC_DIR),
+        }
+    }
+}
+
+/// Configuration for a battery information struct.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct BatteryConfig {
+    /// The name of the battery in Linux's /sys/class/power_supply/ directory.
+    pub battery_id: String,
+
+    /// The level at which the battery is getting low.
+    pub warning_level: BatteryLevel,
+
+    /// The level at which the battery is considered critically low.
+    pub alarm_level: BatteryLevel,
+}
+
+impl Default for BatteryConfig {
+    fn default() -> Self {
+        Self {
+            battery_id: "BAT0".to_string(),
+            warning_level: BatteryLevel::Percentage(0.30),
+            alarm_level: BatteryLevel::Percentage(0.15),
+        }
+    }
+}
+
+/// Configuration for a weather information block.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct WeatherConfig {
+    /// API key for OpenWeatherMap, which, ya know, gets weather information.
+    pub openweathermap_key: String,
+
+    /// API key for IPStack, which returns a geolocation from a user's public IP.
+    pub ipstack_key: String,
+
+    /// Weather icons.
+    pub weather_icons: HashMap<String, char>,
+
+    /// The default icon to use if a weather icon isn't available.
+    pub default_icon: char,
+
+    /// How often to update weather, in minutes.
+    pub update_interval_minutes: u32,
+
+    /// The units to report weather in, either Imperial or Metric.
+    pub units: Units,
+}
+
+impl Default for WeatherConfig {
+    fn default() -> Self {
+        let weather_icons = {
+            let mut hm = HashMap::<String, char>::new();
+            hm.insert(String::from("01d"), '\u{F0599}');
+            hm.insert(String::from("01n"), '\u{F0594}');
+            hm.insert(String::from("02d"), '\u{F0595}');
+            hm.insert(String::from("02n"), '\u{F0F31}');
+            hm.insert(String::from("03d"), '\u{F0590}');
+            hm.insert(String::from("03n"), '\u{F0590}');
+            hm.insert(String::from("04d"), '\u{F0590}');
+            hm.insert(String::from("04n"), '\u{F0590}');
+            hm.insert(String::from("09d"), '\u{F0597}');
+            hm.insert(String::from("09n"), '\u{F0597}');
+            hm.insert(String::from("10d"), '\u{F0596}');
+            hm.insert(String::from("10n"), '\u{F0596}');
+            hm.insert(String::from("11d"), '\u{F0593}');
+            hm.insert(String::from("11n"), '\u{F0593}');
+            hm.insert(String::from("13d"), '\u{F0598}');
+            hm.insert(String::from("13n"), '\u{F0598}');
+            hm.insert(String::from("50d"), '\u{F0591}');
+            hm.insert(String::from("50n"), '\u{F0591}');
+
+            hm
+        };
+
+        Self {
+            // users need to supply their own API keys
+            openweathermap_key: String::new(),
+            ipstack_key: String::new(),
+
+            weather_icons,
+            default_icon: '\u{F0590}',
+            update_interval_minutes: 20,
+
+            // although i'm in the US, the rest of the world uses metric, so let's appeal to the
+            // masses
+            units: Units::Metric,
+        }
+    }
+}
+
+/// Returns the default configuration path for muse-status.
+pub fn default_config_path() -> Result<PathBuf, MuseStatusError> {
+    if let Some(dir) = dirs::config_dir() {
+        Ok(dir.join("muse-status").join("daemon.yaml"))
+    } else {
+        Err(MuseStatusError::Basic(BasicError {
+            message: String::from("couldn't figure out your configuration path.\ntry using the `--config` flag instead")
+        }))
+    }
+}
@@ -1,74 +1,122 @@
+/// A module for watching files and signals.
+pub(crate) mod watch;
+
 use crate::{
     client::ClientMsg,
-    config::Config,
+    config::{BlockConfig, BlockKind, ClickAction, Config},
     errors::*,
     format::{
         self,
-        blocks::{output::BlockOutput, Block, BlockOutputMsg},
+        blocks::{output::BlockOutput, BlockOutputMsg, BlockRequest, ClickEvent, MouseButton},
     },
+    protocol::{self, Capability, Session},
+    timer::TimerCommand,
+    transport::{DaemonAddr, Listener, Stream},
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::sync::{
     mpsc::{self, Receiver, Sender},
     Arc, Mutex,
 };
 use std::thread;
 use std::thread::JoinHandle;
-use std::{
-    io::BufRead,
-    io::Write,
-    net::{TcpListener, TcpStream},
-};
+use std::time::Duration;
 
-type BlockVec = Vec<Box<dyn Block>>;
 type BlockOutputs = HashMap<String, BlockOutput>;
 
+/// A block's output, along with the generation of the block instance that sent it.
+type TaggedOutputMsg = (u64, BlockOutputMsg);
+
+/// How long to wait after the config file changes before reloading it. Editors often save files
+/// in more than one step, so this gives them time to finish.
+const RELOAD_DELAY: Duration = Duration::from_millis(200);
+
+/// The address the daemon listened on by default before it used a Unix socket. Configs written
+/// back then still have it.
+const OLD_DEFAULT_TCP_ADDR: &str = "localhost:2899";
+
 /// A daemon for muse-status. The daemon handles the logic of blocks as a server. Any connected
 /// clients are sent the formatted status output.
 pub struct Daemon {
     config: Config,
+    config_path: PathBuf,
     subscribers: Vec<Subscriber>,
-    update_request_senders: Vec<UpdateRequestSender>,
+    block_request_senders: Vec<BlockRequestSender>,
     block_outputs: BlockOutputs,
+    block_sender: Option<Sender<TaggedOutputMsg>>,
+    banner_sender: Option<Sender<format::Banner>>,
+
+    /// The generation given to the next block that's started. Every block instance has its own,
+    /// so that outputs from a stopped block can be told apart from those of the block replacing
+    /// it, even though both have the same id.
+    next_generation: u64,
 }
 
 type DaemonMutexArc = Arc<Mutex<Daemon>>;
 
 impl Daemon {
-    /// Creates a new Daemon that runs at the specified address.
-    pub fn new(config: Config) -> Self {
+    /// Creates a new Daemon with the config that was read from `config_path`. The file at
+    /// `config_path` is reloaded whenever it changes.
+    pub fn new(config: Config, config_path: PathBuf) -> Self {
         Daemon {
             config,
+            config_path,
             subscribers: Vec::new(),
-            update_request_senders: Vec::new(),
+            block_request_senders: Vec::new(),
             block_outputs: Default::default(),
+            block_sender: None,
+            banner_sender: None,
+            next_generation: 0,
         }
     }
 
-    /// Starts the Daemon with the given blocks by running many asynchronous threads. If starting
-    /// is successful, this function will return a Vec of JoinHandles, which are to be used by
-    /// the calling function.
-    pub fn start(mut self, blocks: BlockVec) -> Result<Vec<JoinHandle<()>>, MuseStatusError> {
+    /// Starts the Daemon and the blocks in its config by running many asynchronous threads. If
+    /// starting is successful, this function will return a Vec of JoinHandles, which are to be
+    /// used by the calling function.
+    pub fn start(mut self) -> Result<Vec<JoinHandle<()>>, MuseStatusError> {
         #[cfg(debug_assertions)]
         println!("the daemon has been started");
 
         // start listening on the daemon's address
-        let listener = TcpListener::bind(&self.config.daemon_addr)?;
+        let listener = Listener::bind(&self.config.daemon_addr)?;
+        println!("listening on {}", self.config.daemon_addr);
+        if self.config.daemon_addr == DaemonAddr::Tcp(String::from(OLD_DEFAULT_TCP_ADDR)) {
+            eprintln!(
+                "heads up: `daemon_addr` is {}, which was the default before the daemon moved to a \
+                 Unix socket. remove it from daemon.yaml to use the socket instead",
+                OLD_DEFAULT_TCP_ADDR
+            );
+        }
 
         // get channels for block outputs and banners
-        let (block_tx, block_rx) = mpsc::channel::<BlockOutputMsg>();
-        let (_banner_tx, banner_rx) = mpsc::channel::<format::Banner>();
+        let (block_tx, block_rx) = mpsc::channel::<TaggedOutputMsg>();
+        let (banner_tx, banner_rx) = mpsc::channel::<format::Banner>();
+        self.block_sender = Some(block_tx);
+        self.banner_sender = Some(banner_tx);
 
         // vector for thread handles
         let mut thread_handles: Vec<JoinHandle<()>> = Vec::new();
 
         // start status blocks
         println!("starting all blocks...");
-        let (mut block_handles, update_request_senders) = self.start_all_blocks(block_tx, blocks);
-        self.update_request_senders = update_request_senders;
+        let mut block_handles = self.start_blocks(self.config.block_configs());
         thread_handles.append(&mut block_handles);
 
+        // reload the config when the file changes or when SIGHUP is received
+        let (reload_tx, reload_rx) = mpsc::channel::<()>();
+        match watch::watch_file(&self.config_path, reload_tx.clone(), || ()) {
+            Ok(handle) => thread_handles.push(handle),
+            Err(e) => eprintln!("couldn't watch the configuration file for changes: {}", e),
+        }
+        match watch::listen_for_sighup(reload_tx) {
+            Ok(handle) => thread_handles.push(handle),
+            Err(e) => eprintln!("couldn't listen for SIGHUP: {}", e),
+        }
+
+        let config_path = self.config_path.clone();
         let daemon_arc_mutex = Arc::new(Mutex::new(self));
 
         // accept connections and handle them, asynchronously
@@ -94,7 +142,7 @@ impl Daemon {
         );
 
         // listen for banners
-        let banners_thread_daemon_mutex = daemon_arc_mutex;
+        let banners_thread_daemon_mutex = daemon_arc_mutex.clone();
         thread_handles.push(
             thread::Builder::new()
                 .name(String::from("banner listener"))
@@ -104,40 +152,141 @@ impl Daemon {
                 .unwrap(),
         );
 
+        // listen for config reloads
+        let reload_thread_daemon_mutex = daemon_arc_mutex;
+        thread_handles.push(
+            thread::Builder::new()
+                .name(String::from("config reloader"))
+                .spawn(move || {
+                    Self::listen_for_reloads(reload_thread_daemon_mutex, &config_path, reload_rx);
+                })
+                .unwrap(),
+        );
+
         Ok(thread_handles)
     }
 
-    fn start_all_blocks(
-        &self,
-        sender: Sender<BlockOutputMsg>,
-        mut blocks: BlockVec,
-    ) -> (Vec<JoinHandle<()>>, Vec<UpdateRequestSender>) {
+    /// Creates and runs the blocks described by `block_configs`. Blocks that can't be created are
+    /// left out, so that the rest of the bar still works.
+    fn start_blocks(&mut self, block_configs: Vec<BlockConfig>) -> Vec<JoinHandle<()>> {
         let mut handles = Vec::new();
-        let mut senders = Vec::new();
 
-        while let Some(b) = blocks.pop() {
-            let name = b.name().to_string();
+        let (sender, banner_sender) = match (&self.block_sender, &self.banner_sender) {
+            (Some(s), Some(b)) => (s.clone(), b.clone()),
+            _ => return handles, // the daemon hasn't been started yet
+        };
+
+        for block_config in block_configs {
+            let mut b = match block_config.to_block() {
+                Ok(b) => b,
+                Err(e) => {
+                    eprintln!("couldn't create block `{}`: {}", block_config.id, e);
+                    continue;
+                }
+            };
+            b.set_banner_sender(banner_sender.clone());
 
             #[cfg(debug_assertions)]
-            println!("==> starting '{}'...", name);
+            println!("==> starting '{}'...", block_config.id);
 
-            let (mut handle_vec, sender) = b.run(sender.clone());
+            let generation = self.next_generation;
+            self.next_generation += 1;
+
+            // the block's outputs are tagged with its generation on their way to the daemon
+            let (output_tx, output_rx) = mpsc::channel::<BlockOutputMsg>();
+            let tagged_sender = sender.clone();
+            let forwarder = thread::Builder::new()
+                .name(format!("{} output forwarder", block_config.id))
+                .spawn(move || {
+                    while let Ok(msg) = output_rx.recv() {
+                        if tagged_sender.send((generation, msg)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            match forwarder {
+                Ok(handle) => handles.push(handle),
+                Err(e) => {
+                    eprintln!("couldn't start block `{}`: {}", block_config.id, e);
+                    continue;
+                }
+            }
+
+            let click_buttons = b.click_buttons();
+            let (mut handle_vec, request_sender) = b.run(output_tx);
 
             handles.append(&mut handle_vec);
-            senders.push(UpdateRequestSender(name, sender));
+            self.block_request_senders.push(BlockRequestSender(
+                block_config,
+                request_sender,
+                generation,
+                click_buttons,
+            ));
+        }
+
+        handles
+    }
+
+    /// Applies a new config. Blocks that were removed or changed are stopped, blocks that were
+    /// added or changed are started, and blocks that stayed the same keep running untouched.
+    /// Afterwards, subscribers are sent all data again.
+    fn reload_config(&mut self, mut config: Config) {
+        if config.daemon_addr != self.config.daemon_addr {
+            eprintln!(
+                "the daemon needs to be restarted to listen on {}",
+                config.daemon_addr
+            );
+            config.daemon_addr = self.config.daemon_addr.clone();
+        }
+
+        let block_configs = config.block_configs();
+
+        // stop blocks that were removed or changed. dropping a block's request sender stops it
+        let mut stopped_ids = Vec::new();
+        self.block_request_senders.retain(|r| {
+            let keep = block_configs.contains(r.config());
+            if !keep {
+                stopped_ids.push(r.id().to_string());
+            }
+            keep
+        });
+        for id in stopped_ids {
+            println!("stopping block `{}`", id);
+            self.block_outputs.remove(&id);
+        }
+
+        // start blocks that were added or changed
+        let new_configs = block_configs
+            .into_iter()
+            .filter(|c| !self.block_request_senders.iter().any(|r| r.config() == c))
+            .collect::<Vec<_>>();
+        for c in &new_configs {
+            println!("starting block `{}`", c.id);
         }
+        self.start_blocks(new_configs);
+
+        self.config = config;
+
+        // click bindings may have changed, and with them the buttons outputs react to
+        let outputs = std::mem::take(&mut self.block_outputs);
+        self.block_outputs = outputs
+            .into_iter()
+            .map(|(name, output)| (name, self.with_click_buttons(output)))
+            .collect();
 
-        (handles, senders)
+        if let Err(e) = self.resend_all_data() {
+            eprintln!("there was an error: {}", e)
+        }
     }
 
     /// Should be run within a separate thread. `self` should NOT be a parameter, as a mutex would
     /// be locked for the entirety of this never-ending function.
-    fn accept_connections(daemon_arc: DaemonMutexArc, listener: &TcpListener) {
+    fn accept_connections(daemon_arc: DaemonMutexArc, listener: &Listener) {
         #[cfg(debug_assertions)]
         println!("listening for connections");
 
-        for result in listener.incoming() {
-            match result {
+        loop {
+            match listener.accept() {
                 Ok(conn) => {
                     if let Err(e) = Self::handle_connection(daemon_arc.clone(), conn) {
                         eprintln!(
@@ -153,11 +302,11 @@ impl Daemon {
 
     /// Should be run within a separate thread. `self` should NOT be a parameter, as a mutex would
     /// be locked for the entirety of this never-ending function.
-    fn listen_to_blocks(daemon_arc: DaemonMutexArc, block_rx: Receiver<BlockOutputMsg>) {
+    fn listen_to_blocks(daemon_arc: DaemonMutexArc, block_rx: Receiver<TaggedOutputMsg>) {
         #[cfg(debug_assertions)]
         println!("listening for block updates");
 
-        while let Ok(msg) = block_rx.recv() {
+        while let Ok((generation, msg)) = block_rx.recv() {
             #[cfg(debug_assertions)]
             println!(
                 "received block update from {}: {:?}",
@@ -166,6 +315,21 @@ impl Daemon {
             );
 
             let mut daemon = daemon_arc.lock().unwrap();
+
+            // a block that was just stopped may still send one last output, even if a block with
+            // the same id has taken its place
+            if !daemon
+                .block_request_senders
+                .iter()
+                .any(|r| r.id() == msg.name() && r.generation() == generation)
+            {
+                continue;
+            }
+
+            let msg = BlockOutputMsg::new(
+                &msg.name(),
+                msg.data().map(|o| daemon.with_click_buttons(o)),
+            );
             if let Some(output) = msg.data() {
                 daemon.block_outputs.insert(msg.name(), output);
             } else {
@@ -180,20 +344,53 @@ impl Daemon {
 
     /// Should be run within a separate thread. `self` should NOT be a parameter, as a mutex would
     /// be locked for the entirety of this never-ending function.
-    fn listen_for_banners(_daemon_arc: DaemonMutexArc, _banner_rx: Receiver<format::Banner>) {
-        todo!()
+    fn listen_for_banners(daemon_arc: DaemonMutexArc, banner_rx: Receiver<format::Banner>) {
+        #[cfg(debug_assertions)]
+        println!("listening for banners");
+
+        while let Ok(banner) = banner_rx.recv() {
+            #[cfg(debug_assertions)]
+            println!("received banner: {:?}", banner);
+
+            let mut daemon = daemon_arc.lock().unwrap();
+            if let Err(e) = daemon.send_banner_to_all(banner) {
+                eprintln!("there was an error: {}", e)
+            }
+        }
+    }
+
+    /// Should be run within a separate thread. Reloads the config whenever a message is received
+    /// on `reload_rx`. If the new config can't be read, the old one stays in use.
+    fn listen_for_reloads(daemon_arc: DaemonMutexArc, config_path: &Path, reload_rx: Receiver<()>) {
+        while reload_rx.recv().is_ok() {
+            // wait for the file to settle, then skip any reloads that piled up in the meantime
+            thread::sleep(RELOAD_DELAY);
+            while reload_rx.try_recv().is_ok() {}
+
+            match Config::from_file(config_path) {
+                Ok(config) => {
+                    println!("reloading the configuration file");
+                    daemon_arc.lock().unwrap().reload_config(config);
+                }
+                Err(e) => eprintln!(
+                    "the configuration file couldn't be reloaded, so the old one is still in use: {}",
+                    e
+                ),
+            }
+        }
     }
 
     fn subscribe_client(
         &mut self,
-        conn: TcpStream,
+        conn: Stream,
+        session: Session,
         collection: Collection,
     ) -> Result<(), MuseStatusError> {
         #[cfg(debug_assertions)]
         println!("a new subscriber requested to connect");
 
         // initialize the subscriber by sending all current data to it
-        let mut sub = Subscriber(conn, collection);
+        let mut sub = Subscriber(conn, collection, session);
         self.force_send_data(&mut sub)?;
 
         // register the subscriber
@@ -204,42 +401,84 @@ impl Daemon {
         Ok(())
     }
 
-    fn handle_connection(
-        daemon_arc: DaemonMutexArc,
-        conn: TcpStream,
-    ) -> Result<(), MuseStatusError> {
+    fn handle_connection(daemon_arc: DaemonMutexArc, conn: Stream) -> Result<(), MuseStatusError> {
         #[cfg(debug_assertions)]
         println!("handling a new connection");
 
-        let mut buf_reader = std::io::BufReader::new(conn.try_clone()?);
-        let mut raw_action = String::new();
-
         thread::Builder::new()
             .name("single client handler".to_string())
             .spawn(move || {
-                buf_reader.read_line(&mut raw_action).unwrap();
+                if let Err(e) = Self::handle_client(daemon_arc, conn) {
+                    eprintln!(
+                        "there was a problem handling a client ({}), but the daemon will keep running",
+                        e
+                    );
+                }
+            })?;
 
-                let action = serde_json::from_str(raw_action.as_str()).unwrap();
+        Ok(())
+    }
 
-                #[cfg(debug_assertions)]
-                println!("handling message from new client: {:?}", action);
+    /// Performs the handshake with a new client, then reads and handles its message. Clients that
+    /// take too long to send either are dropped.
+    fn handle_client(daemon_arc: DaemonMutexArc, mut conn: Stream) -> Result<(), MuseStatusError> {
+        conn.set_read_timeout(Some(protocol::HANDSHAKE_TIMEOUT))?;
+        let session = protocol::daemon_handshake(&mut conn)?;
 
-                let mut daemon = daemon_arc.lock().unwrap();
+        let action = match protocol::read_frame::<_, ClientMsg>(&mut conn)? {
+            Some(a) => a,
+            None => return Ok(()), // the client left without saying anything
+        };
+        conn.set_read_timeout(None)?;
 
-                match action {
-                    ClientMsg::Subscribe(collection) => {
-                        daemon.subscribe_client(conn, collection).unwrap();
-                    }
-                    ClientMsg::Update(collection) => {
-                        #[cfg(debug_assertions)]
-                        println!("handling update request from client: {:?}", collection);
+        #[cfg(debug_assertions)]
+        println!("handling message from new client: {:?}", action);
+
+        if let Some(capability) = action.required_capability() {
+            if !session.supports(capability) {
+                return Err(MuseStatusError::from(BasicError {
+                    message: format!(
+                        "a client sent a {:?} request without negotiating support for it",
+                        capability
+                    ),
+                }));
+            }
+        }
 
-                        daemon.update_collection(&collection);
-                    }
-                    ClientMsg::Noop => (), // literally do nothing
+        let mut daemon = daemon_arc.lock().unwrap();
+
+        match action {
+            ClientMsg::Subscribe(collection) => {
+                daemon.subscribe_client(conn, session, collection)?;
+            }
+            ClientMsg::Update(collection) => {
+                #[cfg(debug_assertions)]
+                println!("handling update request from client: {:?}", collection);
+
+                daemon.update_collection(&collection);
+            }
+            ClientMsg::Banner(banner) => {
+                // banners from clients go through the same queue as banners from blocks
+                if let Some(sender) = &daemon.banner_sender {
+                    sender.send(banner).map_err(|e| BasicError {
+                        message: format!("couldn't queue banner: {}", e),
+                    })?;
                 }
-            })
-            .unwrap();
+            }
+            ClientMsg::Click(event) => {
+                #[cfg(debug_assertions)]
+                println!("handling click from client: {:?}", event);
+
+                daemon.click_block(event);
+            }
+            ClientMsg::Timer { block, command } => {
+                #[cfg(debug_assertions)]
+                println!("handling timer command from client: {:?}", command);
+
+                daemon.send_timer_command(block.as_deref(), &command);
+            }
+            ClientMsg::Noop => (), // literally do nothing
+        }
 
         Ok(())
     }
@@ -253,13 +492,27 @@ impl Daemon {
         println!("sending output to all subscribers: {:?}", new_block_output);
 
         let block_name = new_block_output.name();
-        let serialized_output = serde_json::to_string(&DaemonMsg::NewOutput(new_block_output))?;
+        let data = new_block_output.data();
+        let serialized_output = serde_json::to_vec(&DaemonMsg::NewOutput(new_block_output))?;
         let config = &self.config;
 
         // send updates, only retaining subscribers that were successfully sent updates
         self.subscribers.retain_mut(|sub| {
             if is_block_name_in_collection(config, &block_name, sub.collection()) {
-                if let Err(e) = send_serialized_data(sub, &serialized_output) {
+                // outputs using features the subscriber lacks are sent in a form it understands
+                let result = match &data {
+                    Some(o) if needs_downgrade(o, sub.session()) => {
+                        let output = output_for_session(o.clone(), sub.session());
+                        let msg =
+                            DaemonMsg::NewOutput(BlockOutputMsg::new(&block_name, Some(output)));
+                        serde_json::to_vec(&msg)
+                            .map_err(MuseStatusError::from)
+                            .and_then(|bytes| send_serialized_data(sub, &bytes))
+                    }
+                    _ => send_serialized_data(sub, &serialized_output),
+                };
+
+                if let Err(e) = result {
                     eprintln!(
                         "there was an error ({}). the subscriber will be ignored from now on",
                         e
@@ -281,56 +534,281 @@ impl Daemon {
         Ok(())
     }
 
+    /// Sends a banner to every subscriber that can display banners, regardless of the collection
+    /// they subscribed to.
+    fn send_banner_to_all(&mut self, banner: format::Banner) -> Result<(), MuseStatusError> {
+        let serialized_banner = serde_json::to_vec(&DaemonMsg::Banner(banner))?;
+
+        self.subscribers.retain_mut(|sub| {
+            if !sub.session().supports(Capability::Banners) {
+                return true;
+            }
+
+            if let Err(e) = send_serialized_data(sub, &serialized_banner) {
+                eprintln!(
+                    "there was an error ({}). the subscriber will be ignored from now on",
+                    e
+                );
+                false
+            } else {
+                true
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Tells subscribers that the config was reloaded, then sends them all of their data again.
+    fn resend_all_data(&mut self) -> Result<(), MuseStatusError> {
+        let serialized_reload = serde_json::to_vec(&DaemonMsg::ConfigReloaded)?;
+        let config = &self.config;
+        let block_outputs = &self.block_outputs;
+
+        self.subscribers.retain_mut(|sub| {
+            let result = if sub.session().supports(Capability::ConfigReload) {
+                send_serialized_data(sub, &serialized_reload)
+            } else {
+                Ok(())
+            }
+            .and_then(|_| {
+                let msg = DaemonMsg::AllData(outputs_in_collection(
+                    config,
+                    block_outputs,
+                    sub.collection(),
+                    sub.session(),
+                ));
+                send_serialized_data(sub, &serde_json::to_vec(&msg)?)
+            });
+
+            if let Err(e) = result {
+                eprintln!(
+                    "there was an error ({}). the subscriber will be ignored from now on",
+                    e
+                );
+                false
+            } else {
+                true
+            }
+        });
+
+        Ok(())
+    }
+
     /// Sends all data requested by the subscriber, usually to initialize it.
     fn force_send_data(&self, sub: &mut Subscriber) -> Result<(), MuseStatusError> {
-        let all_outputs = self
-            .block_outputs
-            .values()
-            .map(|v| v.to_owned())
-            .collect::<Vec<BlockOutput>>();
-        let msg = DaemonMsg::AllData(all_outputs);
-        send_serialized_data(sub, &serde_json::to_string(&msg)?)
+        let msg = DaemonMsg::AllData(outputs_in_collection(
+            &self.config,
+            &self.block_outputs,
+            sub.collection(),
+            sub.session(),
+        ));
+        send_serialized_data(sub, &serde_json::to_vec(&msg)?)
     }
 
     fn update_collection(&mut self, collection: &Collection) {
         // get the iterator of requesters to use according to the collection
-        let all_requesters = self.update_request_senders.iter_mut();
+        let all_requesters = self.block_request_senders.iter_mut();
         let config = &self.config;
-        let requesters: Vec<&mut UpdateRequestSender> = all_requesters
-            .filter(|r| is_block_name_in_collection(config, &r.0, collection))
+        let requesters: Vec<&mut BlockRequestSender> = all_requesters
+            .filter(|r| is_block_name_in_collection(config, r.id(), collection))
             .collect();
 
         for requester in requesters {
-            if let Err(e) = requester.send() {
+            if let Err(e) = requester.send(BlockRequest::Update) {
                 eprintln!("updating error: {}", e)
             }
         }
     }
+
+    /// Runs the action bound to the click in the config. If there is none, the click is forwarded
+    /// to the block that was clicked.
+    fn click_block(&mut self, event: ClickEvent) {
+        let binding = self
+            .config
+            .click_bindings
+            .get(&event.block_name)
+            .and_then(|b| b.get(&event.button))
+            .cloned();
+
+        if let Some(action) = binding {
+            if let Err(e) = self.run_click_action(&event, action) {
+                eprintln!("couldn't run click binding: {}", e)
+            }
+
+            return;
+        }
+
+        match self
+            .block_request_senders
+            .iter_mut()
+            .find(|r| r.id() == event.block_name)
+        {
+            Some(requester) => {
+                if let Err(e) = requester.send(BlockRequest::Click(event)) {
+                    eprintln!("clicking error: {}", e)
+                }
+            }
+            None => eprintln!(
+                "a block named `{}` was clicked, but it isn't running",
+                event.block_name
+            ),
+        }
+    }
+
+    /// Returns `output` with the buttons that do something when it's clicked: those its block
+    /// reacts to, and those with click bindings.
+    fn with_click_buttons(&self, output: BlockOutput) -> BlockOutput {
+        let name = output.name();
+        let mut buttons = self
+            .block_request_senders
+            .iter()
+            .find(|r| r.id() == name)
+            .map(|r| r.click_buttons().to_vec())
+            .unwrap_or_default();
+        if let Some(bindings) = self.config.click_bindings.get(&name) {
+            buttons.extend(bindings.keys().copied());
+        }
+
+        buttons.sort_by_key(MouseButton::x11_code);
+        buttons.dedup();
+        output.with_buttons(buttons)
+    }
+
+    /// Sends a timer command to the timer block with the id `block`, or to the first running
+    /// timer block if it's `None`.
+    fn send_timer_command(&mut self, block: Option<&str>, command: &TimerCommand) {
+        let requester = self.block_request_senders.iter_mut().find(|r| {
+            matches!(r.config().kind, BlockKind::Timer(_)) && block.is_none_or(|b| r.id() == b)
+        });
+
+        let requester = match (requester, block) {
+            (Some(requester), _) => requester,
+            (None, Some(block)) => {
+                eprintln!(
+                    "a timer command was sent to `{}`, but no timer block by that id is running",
+                    block
+                );
+                return;
+            }
+            (None, None) => {
+                eprintln!("a timer command was sent, but no timer block is running");
+                return;
+            }
+        };
+
+        match serde_json::to_string(command) {
+            Ok(command) => {
+                if let Err(e) = requester.send(BlockRequest::Command(command)) {
+                    eprintln!("timer error: {}", e)
+                }
+            }
+            Err(e) => eprintln!("timer error: {}", e),
+        }
+    }
+
+    /// Runs an action from the config's click bindings.
+    fn run_click_action(
+        &mut self,
+        event: &ClickEvent,
+        action: ClickAction,
+    ) -> Result<(), MuseStatusError> {
+        match action {
+            ClickAction::Command(command) => {
+                let mut child = Command::new("sh")
+                    .arg("-c")
+                    .arg(&command)
+                    .env("MUSE_BLOCK", &event.block_name)
+                    .env("MUSE_BUTTON", event.button.to_string())
+                    .spawn()?;
+
+                // wait for the command in another thread so that it doesn't become a zombie
+                // and doesn't hold up the daemon
+                thread::Builder::new()
+                    .name(String::from("click command"))
+                    .spawn(move || match child.wait() {
+                        Ok(status) if !status.success() => {
+                            eprintln!("click command `{}` exited with {}", command, status)
+                        }
+                        Ok(_) => (),
+                        Err(e) => eprintln!("couldn't wait for click command: {}", e),
+                    })?;
+            }
+            ClickAction::Update(names) => {
+                let collection = if names.is_empty() {
+                    Collection::One(event.block_name.clone())
+                } else {
+                    Collection::Many(names)
+                };
+
+                self.update_collection(&collection);
+            }
+            ClickAction::Banner(banner) => {
+                if let Some(sender) = &self.banner_sender {
+                    sender.send(banner).map_err(|e| BasicError {
+                        message: format!("couldn't queue banner: {}", e),
+                    })?;
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
 
-/// A struct containing a TcpStream to send data to. The collection defines what data the
-/// subscriber receives.
-struct Subscriber(TcpStream, Collection);
+/// A struct containing a Stream to send data to. The collection defines what data the
+/// subscriber receives, and the session defines which messages the subscriber understands.
+struct Subscriber(Stream, Collection, Session);
 
 impl Subscriber {
-    /// Convenience function to get the Subscriber's TcpStream.
-    fn stream(&self) -> &TcpStream {
-        &self.0
+    /// Convenience function to get the Subscriber's Stream.
+    fn stream(&mut self) -> &mut Stream {
+        &mut self.0
     }
 
     /// Convenience function to get the Subscriber's requested Collection.
     fn collection(&self) -> &Collection {
         &self.1
     }
+
+    /// Convenience function to get the Subscriber's negotiated Session.
+    fn session(&self) -> &Session {
+        &self.2
+    }
 }
 
-/// A struct/tuple for a block update request sender.
-struct UpdateRequestSender(String, Sender<()>);
+/// A struct/tuple for a block request sender, along with the config the block was created from,
+/// the block's generation and the buttons it reacts to.
+struct BlockRequestSender(
+    BlockConfig,
+    Sender<BlockRequest>,
+    u64,
+    &'static [MouseButton],
+);
+
+impl BlockRequestSender {
+    /// Convenience function to get the block's config.
+    fn config(&self) -> &BlockConfig {
+        &self.0
+    }
 
-impl UpdateRequestSender {
-    /// Convenience function for sending update requests.
-    fn send(&mut self) -> Result<(), mpsc::SendError<()>> {
-        self.1.send(())
+    /// Convenience function to get the block's id.
+    fn id(&self) -> &str {
+        &self.0.id
+    }
+
+    /// Convenience function to get the block's generation.
+    fn generation(&self) -> u64 {
+        self.2
+    }
+
+    /// Convenience function to get the buttons the block reacts to.
+    fn click_buttons(&self) -> &'static [MouseButton] {
+        self.3
+    }
+
+    /// Convenience function for sending requests.
+    fn send(&mut self, request: BlockRequest) -> Result<(), mpsc::SendError<BlockRequest>> {
+        self.1.send(request)
     }
 }
 
@@ -364,6 +842,13 @@ pub enum DaemonMsg {
 
     /// A Vec of BlockOutputs for all data currently known by the daemon.
     AllData(Vec<BlockOutput>),
+
+    /// A banner to display in place of the bar. Only sent to clients that support banners.
+    Banner(format::Banner),
+
+    /// The daemon's config file was reloaded, so clients should reload theirs too. Followed by
+    /// `AllData`. Only sent to clients that support config reloads.
+    ConfigReloaded,
 }
 
 /// A collection of outputs from blocks to be formatted
@@ -386,6 +871,26 @@ pub enum DataPayload {
 }
 
 impl DataPayload {
+    /// Returns every output in the payload, in no particular order.
+    pub fn outputs(&self) -> Vec<&BlockOutput> {
+        match self {
+            Self::Ranked {
+                primary,
+                secondary,
+                tertiary,
+            } => primary.iter().chain(secondary).chain(tertiary).collect(),
+            Self::Unranked(outputs) => outputs.iter().collect(),
+        }
+    }
+
+    /// Adds an output after all tertiary-ranked outputs (or after all outputs, if unranked).
+    pub fn push_tertiary(&mut self, output: BlockOutput) {
+        match self {
+            Self::Ranked { tertiary, .. } => tertiary.push(output),
+            Self::Unranked(outputs) => outputs.push(output),
+        }
+    }
+
     /// Creates a Ranked DataPayload out of the outputs provided.
     pub fn ranked(config: &Config, outputs: &BlockOutputs) -> Self {
         let (primary, secondary, tertiary) = (
@@ -464,13 +969,43 @@ fn is_block_name_in_collection(config: &Config, block_name: &str, collection: &C
     }
 }
 
+/// Returns the outputs of the blocks in `collection`, in a form the other side of `session`
+/// understands.
+fn outputs_in_collection(
+    config: &Config,
+    outputs: &BlockOutputs,
+    collection: &Collection,
+    session: &Session,
+) -> Vec<BlockOutput> {
+    outputs
+        .iter()
+        .filter(|(name, _)| is_block_name_in_collection(config, name, collection))
+        .map(|(_, output)| output_for_session(output.clone(), session))
+        .collect()
+}
+
+/// Returns true if `output` uses something the other side of `session` doesn't support.
+fn needs_downgrade(output: &BlockOutput, session: &Session) -> bool {
+    (output.text().is_progress() && !session.supports(Capability::Progress))
+        || (output.has_animation() && !session.supports(Capability::Animations))
+}
+
+/// Returns `output` without anything the other side of `session` doesn't support: progress bars
+/// become percentages, and animations are left out.
+fn output_for_session(mut output: BlockOutput, session: &Session) -> BlockOutput {
+    if !session.supports(Capability::Progress) {
+        output = output.without_progress();
+    }
+    if !session.supports(Capability::Animations) {
+        output = output.without_animation();
+    }
+
+    output
+}
+
 fn send_serialized_data(
     sub: &mut Subscriber,
-    serialized_data: &str,
+    serialized_data: &[u8],
 ) -> Result<(), MuseStatusError> {
-    // add a new line to the end of the data so that clients can parse correctly
-    let out = format!("{}\n", serialized_data);
-    sub.stream()
-        .write_all(out.as_bytes())
-        .map_err(MuseStatusError::from)
+    protocol::write_frame_bytes(sub.stream(), serialized_data)
 }
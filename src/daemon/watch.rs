@@ -0,0 +1,81 @@
+use crate::errors::*;
+use inotify::{Inotify, WatchMask};
+use signal_hook::{consts::SIGHUP, iterator::Signals};
+use std::{
+    path::Path,
+    sync::mpsc::Sender,
+    thread::{self, JoinHandle},
+};
+
+/// Watches the file at `path` with inotify, sending `message()` on `tx` whenever it changes.
+///
+/// The file's directory is watched instead of the file itself, because many editors save by
+/// writing a new file and renaming it over the old one, which would end a watch on the file.
+pub fn watch_file<M: Send + 'static>(
+    path: &Path,
+    tx: Sender<M>,
+    message: fn() -> M,
+) -> Result<JoinHandle<()>, MuseStatusError> {
+    let dir = path.parent().ok_or_else(|| BasicError {
+        message: format!("{} has no parent directory", path.display()),
+    })?;
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| BasicError {
+            message: format!("{} isn't a file", path.display()),
+        })?
+        .to_owned();
+
+    let mut inotify = Inotify::init()?;
+    inotify.add_watch(
+        dir,
+        WatchMask::CLOSE_WRITE | WatchMask::MOVED_TO | WatchMask::CREATE,
+    )?;
+
+    let watched = path.display().to_string();
+    let handle = thread::Builder::new()
+        .name(format!("{} watcher", file_name.to_string_lossy()))
+        .spawn(move || {
+            let mut buffer = [0; 4096];
+            loop {
+                let events = match inotify.read_events_blocking(&mut buffer) {
+                    Ok(e) => e,
+                    Err(e) => {
+                        eprintln!("stopped watching {}: {}", watched, e);
+                        return;
+                    }
+                };
+
+                if events.into_iter().any(|e| e.name == Some(&file_name)) {
+                    #[cfg(debug_assertions)]
+                    eprintln!("{} changed", watched);
+
+                    if tx.send(message()).is_err() {
+                        return;
+                    }
+                }
+            }
+        })?;
+
+    Ok(handle)
+}
+
+/// Sends on `reload_tx` whenever the daemon receives SIGHUP.
+pub fn listen_for_sighup(reload_tx: Sender<()>) -> Result<JoinHandle<()>, MuseStatusError> {
+    let mut signals = Signals::new([SIGHUP])?;
+
+    let handle = thread::Builder::new()
+        .name(String::from("SIGHUP listener"))
+        .spawn(move || {
+            for _ in signals.forever() {
+                #[cfg(debug_assertions)]
+                eprintln!("received SIGHUP");
+
+                if reload_tx.send(()).is_err() {
+                    return;
+                }
+            }
+        })?;
+
+    Ok(handle)
+}
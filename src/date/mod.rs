@@ -10,7 +10,8 @@ use chrono::{prelude::*, DateTime, Duration, Local};
 /// The format with which to format time strings.
 pub const TIME_FORMAT: &str = "%-I:%M %P";
 const DATE_FORMAT: &str = "%a, %b %-d";
-const CLOCK_ICONS: [char; 12] = [
+/// Clock faces for each hour, from 12 o'clock to 11 o'clock.
+pub(crate) const CLOCK_ICONS: [char; 12] = [
     '\u{F1456}',
     '\u{F144B}',
     '\u{F144C}',
@@ -27,6 +28,7 @@ const CLOCK_ICONS: [char; 12] = [
 
 /// Transmits time and date data.
 pub struct DateBlock {
+    id: String,
     now: DateTime<Local>,
     next_update: DateTime<Local>,
 }
@@ -36,7 +38,11 @@ impl Default for DateBlock {
         let now = Local::now();
         let next_update = next_minute_or_five_seconds();
 
-        Self { now, next_update }
+        Self {
+            id: String::from("date"),
+            now,
+            next_update,
+        }
     }
 }
 
@@ -45,6 +51,12 @@ impl DateBlock {
     pub fn new() -> Self {
         Default::default()
     }
+
+    /// Sets the id of the block, which is used as its name. By default, the id is `date`.
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = id.to_string();
+        self
+    }
 }
 
 impl Block for DateBlock {
@@ -57,7 +69,7 @@ impl Block for DateBlock {
     }
 
     fn name(&self) -> &str {
-        "date"
+        &self.id
     }
 
     fn next_update(&self) -> Option<NextUpdate> {
@@ -97,7 +109,7 @@ fn get_greeting() -> &'static str {
 }
 
 /// Returns the time of the next minute of the hour.
-fn get_next_minute() -> DateTime<Local> {
+pub(crate) fn get_next_minute() -> DateTime<Local> {
     let now = Local::now();
     let in_one_minute = now + Duration::minutes(1);
     if let Some(truncated) = in_one_minute.with_second(0) {
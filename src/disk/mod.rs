@@ -0,0 +1,262 @@
+use crate::{
+    config::{DiskConfig, MountConfig},
+    errors::*,
+    format::{
+        blocks::{output::*, *},
+        Attention,
+    },
+};
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use std::{ffi::CString, io, mem::MaybeUninit, os::unix::ffi::OsStrExt, path::Path};
+
+const DISK_ICON: char = '\u{F02CA}';
+const BYTES_PER_GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// An amount of free space left on a mount, whether measured by percentage or size.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialOrd, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiskLevel {
+    /// The share of the mount that's free, from 0.0 to 1.0.
+    PercentageFree(f32),
+
+    /// The free space on the mount, in GiB.
+    GibFree(f64),
+}
+
+/// The size, used and free space of a mount, in bytes.
+#[derive(Clone, Copy)]
+struct Usage {
+    total: u64,
+    used: u64,
+    free: u64,
+}
+
+impl Usage {
+    /// Returns the usage of the filesystem mounted at `path`, from statvfs. Free space is what's
+    /// available to unprivileged users, which leaves out space reserved for root.
+    fn of(path: &Path) -> Result<Self, MuseStatusError> {
+        let c_path = CString::new(path.as_os_str().as_bytes()).map_err(|_| BasicError {
+            message: format!("{} isn't a valid path", path.display()),
+        })?;
+
+        let mut stat = MaybeUninit::<libc::statvfs>::uninit();
+
+        // SAFETY: `c_path` is a valid C string and `stat` is only read if statvfs succeeds, in
+        // which case it has been filled in
+        let stat = unsafe {
+            if libc::statvfs(c_path.as_ptr(), stat.as_mut_ptr()) != 0 {
+                return Err(MuseStatusError::from(io::Error::last_os_error()));
+            }
+            stat.assume_init()
+        };
+
+        let fragment_size = stat.f_frsize as u64;
+        Ok(Self {
+            total: stat.f_blocks as u64 * fragment_size,
+            used: (stat.f_blocks as u64).saturating_sub(stat.f_bfree as u64) * fragment_size,
+            free: stat.f_bavail as u64 * fragment_size,
+        })
+    }
+
+    /// Returns true if the free space is at or below `level`.
+    fn is_at_or_below(&self, level: &DiskLevel) -> bool {
+        match level {
+            DiskLevel::PercentageFree(p) => {
+                self.total > 0 && (self.free as f64 / self.total as f64) <= *p as f64
+            }
+            DiskLevel::GibFree(gib) => (self.free as f64 / BYTES_PER_GIB) <= *gib,
+        }
+    }
+}
+
+/// A mount as of the last update.
+struct MountRead {
+    label: String,
+
+    /// The usage of the mount, or `None` if it couldn't be read.
+    usage: Option<Usage>,
+    attention: Attention,
+}
+
+/// A block that shows the free space on mounts. The most urgent mount is shown, and clicking
+/// the block cycles through the rest.
+pub struct DiskBlock {
+    id: String,
+    config: DiskConfig,
+    reads: Vec<MountRead>,
+
+    /// The index (in `reads`) of the mount chosen by clicking, if any. If `None`, the most urgent
+    /// mount is shown.
+    selected: Option<usize>,
+}
+
+impl DiskBlock {
+    /// Returns a new block with the configuration provided.
+    pub fn new(config: DiskConfig) -> Self {
+        Self {
+            id: String::from("disk"),
+            config,
+            reads: Vec::new(),
+            selected: None,
+        }
+    }
+
+    /// Sets the id of the block, which is used as its name. By default, the id is `disk`.
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = id.to_string();
+        self
+    }
+
+    fn read_mount(&self, mount: &MountConfig) -> Result<MountRead, MuseStatusError> {
+        let usage = Usage::of(Path::new(&mount.path))?;
+
+        let warning_level = mount.warning_level.unwrap_or(self.config.warning_level);
+        let alarm_level = mount.alarm_level.unwrap_or(self.config.alarm_level);
+        let attention = if usage.is_at_or_below(&alarm_level) {
+            Attention::Alarm
+        } else if usage.is_at_or_below(&warning_level) {
+            Attention::Warning
+        } else {
+            Attention::Normal
+        };
+
+        Ok(MountRead {
+            label: mount_label(mount),
+            usage: Some(usage),
+            attention,
+        })
+    }
+
+    /// Returns the index of the mount to show, out of those in `reads`.
+    fn shown_index(&self) -> Option<usize> {
+        if let Some(i) = self.selected.filter(|i| *i < self.reads.len()) {
+            return Some(i);
+        }
+
+        // alarms first, then warnings, then whichever has the least space free
+        self.reads
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                urgency(&b.attention)
+                    .cmp(&urgency(&a.attention))
+                    .then(free(a).cmp(&free(b)))
+            })
+            .map(|(i, _)| i)
+    }
+}
+
+impl Block for DiskBlock {
+    fn name(&self) -> &str {
+        &self.id
+    }
+
+    fn update(&mut self) -> Result<(), UpdateError> {
+        let mut reads = Vec::new();
+        let mut errors = Vec::new();
+        for mount in &self.config.mounts {
+            // an unreadable mount is flagged rather than hiding the others
+            let read = self.read_mount(mount).unwrap_or_else(|e| {
+                errors.push(format!("couldn't read {}: {}", mount.path, e));
+                MountRead {
+                    label: mount_label(mount),
+                    usage: None,
+                    attention: Attention::Warning,
+                }
+            });
+
+            if self.config.hide_until_warning && read.attention == Attention::Normal {
+                continue;
+            }
+            reads.push(read);
+        }
+
+        // the clicked mount may have been hidden since
+        if self.reads.len() != reads.len() {
+            self.selected = None;
+        }
+        self.reads = reads;
+
+        if !errors.is_empty() && errors.len() == self.config.mounts.len() {
+            return Err(UpdateError {
+                block_name: self.name().to_string(),
+                message: errors.join(", "),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn click(&mut self, event: &ClickEvent) -> Result<(), UpdateError> {
+        if event.button == click::MouseButton::Left && !self.reads.is_empty() {
+            let next = self.shown_index().map(|i| i + 1).unwrap_or(0);
+            self.selected = Some(next % self.reads.len());
+        }
+
+        Ok(())
+    }
+
+    fn click_buttons(&self) -> &'static [click::MouseButton] {
+        &[click::MouseButton::Left]
+    }
+
+    fn next_update(&self) -> Option<NextUpdate> {
+        Some(NextUpdate::In(Duration::seconds(
+            self.config.update_interval_seconds.max(1).into(),
+        )))
+    }
+
+    fn output(&self) -> Option<BlockOutput> {
+        let read = &self.reads[self.shown_index()?];
+        let text = match &read.usage {
+            Some(usage) => BlockText::Pair(
+                format!("{} free", format_bytes(usage.free)),
+                format!("{} used on {}", format_bytes(usage.used), read.label),
+            ),
+            None => BlockText::Pair(String::from("unreadable"), read.label.clone()),
+        };
+
+        Some(BlockOutput::new(
+            self.name(),
+            Some(DISK_ICON),
+            text,
+            read.attention.clone(),
+        ))
+    }
+}
+
+fn mount_label(mount: &MountConfig) -> String {
+    mount.label.clone().unwrap_or_else(|| mount.path.clone())
+}
+
+/// Returns the free space on the mount read, counting an unreadable mount as having none.
+fn free(read: &MountRead) -> u64 {
+    read.usage.map_or(0, |u| u.free)
+}
+
+fn urgency(attention: &Attention) -> u8 {
+    match attention {
+        Attention::Alarm | Attention::AlarmPulse => 2,
+        Attention::Warning | Attention::WarningPulse => 1,
+        _ => 0,
+    }
+}
+
+/// Formats a number of bytes like `850 MiB`, `4.2 GiB` or `120 GiB`.
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if value < 10.0 && unit > 0 {
+        format!("{:.1} {}", value, UNITS[unit])
+    } else {
+        format!("{:.0} {}", value, UNITS[unit])
+    }
+}
@@ -27,6 +27,9 @@ pub enum MuseStatusError {
 
     /// Wraps an error from parsing colors.
     RGBAParse(RGBAParseError),
+
+    /// Wraps an error from the daemon/client protocol.
+    Protocol(ProtocolError),
 }
 
 impl From<String> for MuseStatusError {
@@ -79,6 +82,12 @@ impl From<RGBAParseError> for MuseStatusError {
     }
 }
 
+impl From<ProtocolError> for MuseStatusError {
+    fn from(e: ProtocolError) -> Self {
+        Self::Protocol(e)
+    }
+}
+
 impl Display for MuseStatusError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
@@ -88,6 +97,7 @@ impl Display for MuseStatusError {
             Self::ParseInt(p) => p.fmt(f),
             Self::Reqwest(r) => r.fmt(f),
             Self::RGBAParse(r) => r.fmt(f),
+            Self::Protocol(p) => p.fmt(f),
         }
     }
 }
@@ -137,3 +147,48 @@ impl From<SendError<BlockOutput>> for UpdateError {
 }
 
 impl Error for UpdateError {}
+
+/// ProtocolError is returned when the daemon and a client can't understand each other.
+#[derive(Debug)]
+pub enum ProtocolError {
+    /// The two sides speak protocol versions that don't overlap.
+    Incompatible {
+        /// The protocol version spoken by this build.
+        ours: u32,
+
+        /// The protocol version spoken by the other side.
+        theirs: u32,
+    },
+
+    /// The other side closed the connection (or never answered) before finishing the handshake.
+    NoHandshake,
+
+    /// The other side speaks the old newline-delimited protocol, from before handshakes existed.
+    Legacy,
+
+    /// A frame was larger than the protocol allows.
+    FrameTooLarge(usize),
+}
+
+impl Display for ProtocolError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Incompatible { ours, theirs } => write!(
+                f,
+                "the daemon and client are incompatible (this build speaks protocol version {}, the other side speaks version {}). make sure muse-status and muse-status-daemon come from the same build",
+                ours, theirs
+            ),
+            Self::NoHandshake => write!(
+                f,
+                "the other side didn't complete the handshake. it may be an older build of muse-status"
+            ),
+            Self::Legacy => write!(
+                f,
+                "the other side speaks the old newline-delimited protocol. make sure muse-status and muse-status-daemon come from the same build"
+            ),
+            Self::FrameTooLarge(len) => write!(f, "refusing to handle a frame of {} bytes", len),
+        }
+    }
+}
+
+impl Error for ProtocolError {}
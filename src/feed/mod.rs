@@ -0,0 +1,429 @@
+use crate::{
+    config::FeedConfig,
+    errors::*,
+    format::{
+        animation::Animation,
+        blocks::{output::*, *},
+        Attention,
+    },
+};
+use inotify::{Inotify, WatchMask};
+use serde::Deserialize;
+use std::{
+    ffi::{CString, OsString},
+    fs::{self, File, OpenOptions},
+    io::{self, ErrorKind, Read},
+    os::unix::{
+        ffi::OsStrExt,
+        fs::{FileTypeExt, OpenOptionsExt},
+        io::{AsRawFd, RawFd},
+    },
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Sender, TryRecvError},
+    thread::{self, JoinHandle},
+    time::{Duration, SystemTime},
+};
+
+/// How long the block waits for new payloads before checking whether it should stop or expire.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What other programs write to a feed block.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Payload {
+    icon: Option<char>,
+    text: PayloadText,
+    #[serde(default)]
+    attention: Attention,
+    animation: Option<Animation>,
+
+    /// Overrides the block's `expire_seconds` for this payload.
+    expire_seconds: Option<f64>,
+}
+
+/// The text of a payload: either a plain string, or a `BlockText` like `{"Pair": ["a", "b"]}`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PayloadText {
+    Plain(String),
+    Block(BlockText),
+}
+
+/// Where payloads come from.
+enum Source {
+    /// A named pipe, where each line is a payload. `partial` holds a line that hasn't ended yet.
+    Fifo { fifo: File, partial: Vec<u8> },
+
+    /// A file, whose whole content is the payload. Its directory is watched, like the config
+    /// file is, so that files replaced by renaming are noticed too.
+    File {
+        path: PathBuf,
+        file_name: OsString,
+        inotify: Inotify,
+    },
+}
+
+impl Source {
+    fn open(config: &FeedConfig) -> Result<Self, MuseStatusError> {
+        let path = PathBuf::from(&config.path);
+
+        if config.fifo {
+            let is_fifo = fs::metadata(&path).map(|m| m.file_type().is_fifo());
+            match is_fifo {
+                Ok(true) => (),
+                Ok(false) => {
+                    return Err(MuseStatusError::from(format!(
+                        "{} exists, but isn't a named pipe",
+                        path.display()
+                    )))
+                }
+                Err(e) if e.kind() == ErrorKind::NotFound => make_fifo(&path)?,
+                Err(e) => return Err(e.into()),
+            }
+
+            // opening for writing too means the pipe is never closed when writers go away
+            let fifo = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .custom_flags(libc::O_NONBLOCK)
+                .open(&path)?;
+
+            return Ok(Self::Fifo {
+                fifo,
+                partial: Vec::new(),
+            });
+        }
+
+        let dir = path.parent().ok_or_else(|| BasicError {
+            message: format!("{} has no parent directory", path.display()),
+        })?;
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| BasicError {
+                message: format!("{} isn't a file", path.display()),
+            })?
+            .to_owned();
+
+        let mut inotify = Inotify::init()?;
+        inotify.add_watch(
+            dir,
+            WatchMask::CLOSE_WRITE
+                | WatchMask::MOVED_TO
+                | WatchMask::CREATE
+                | WatchMask::DELETE
+                | WatchMask::MOVED_FROM,
+        )?;
+
+        Ok(Self::File {
+            path,
+            file_name,
+            inotify,
+        })
+    }
+
+    fn fd(&self) -> RawFd {
+        match self {
+            Self::Fifo { fifo, .. } => fifo.as_raw_fd(),
+            Self::File { inotify, .. } => inotify.as_raw_fd(),
+        }
+    }
+
+    /// Waits up to `timeout` for new payloads, returning them along with when they were written.
+    fn wait(&mut self, timeout: Duration) -> Result<Vec<(String, SystemTime)>, MuseStatusError> {
+        if !wait_readable(self.fd(), timeout)? {
+            return Ok(Vec::new());
+        }
+
+        match self {
+            Self::Fifo { fifo, partial } => {
+                let mut buffer = [0; 4096];
+                loop {
+                    match fifo.read(&mut buffer) {
+                        Ok(0) => break,
+                        Ok(n) => partial.extend_from_slice(&buffer[..n]),
+                        Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                        Err(e) => return Err(e.into()),
+                    }
+                }
+
+                let mut payloads = Vec::new();
+                while let Some(end) = partial.iter().position(|b| *b == b'\n') {
+                    let line = partial.drain(..=end).collect::<Vec<_>>();
+                    payloads.push((
+                        String::from_utf8_lossy(&line).into_owned(),
+                        SystemTime::now(),
+                    ));
+                }
+
+                Ok(payloads)
+            }
+            Self::File {
+                file_name, inotify, ..
+            } => {
+                let mut buffer = [0; 4096];
+                let changed = match inotify.read_events(&mut buffer) {
+                    Ok(events) => events.into_iter().any(|e| e.name == Some(file_name)),
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => false,
+                    Err(e) => return Err(e.into()),
+                };
+
+                if changed {
+                    Ok(vec![self.read_file()?])
+                } else {
+                    Ok(Vec::new())
+                }
+            }
+        }
+    }
+
+    /// Reads the whole file, returning its content and when it was last written. A missing file
+    /// counts as empty.
+    fn read_file(&self) -> Result<(String, SystemTime), MuseStatusError> {
+        let path = match self {
+            Self::File { path, .. } => path,
+            Self::Fifo { .. } => return Ok((String::new(), SystemTime::now())),
+        };
+
+        match fs::read_to_string(path) {
+            Ok(content) => {
+                let modified = fs::metadata(path)?.modified()?;
+                Ok((content, modified))
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok((String::new(), SystemTime::now())),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+fn make_fifo(path: &Path) -> Result<(), MuseStatusError> {
+    let c_path = CString::new(path.as_os_str().as_bytes()).map_err(|_| BasicError {
+        message: format!("{} isn't a valid path", path.display()),
+    })?;
+
+    // SAFETY: `c_path` is a valid C string
+    if unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) } != 0 {
+        return Err(MuseStatusError::from(io::Error::last_os_error()));
+    }
+
+    Ok(())
+}
+
+/// Waits up to `timeout` for `fd` to have something to read. Returns true if it does.
+fn wait_readable(fd: RawFd, timeout: Duration) -> Result<bool, MuseStatusError> {
+    let mut poll_fd = libc::pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    // rounding up means waiting until a deadline doesn't end just short of it
+    let timeout_ms = timeout.as_micros().div_ceil(1000).min(i32::MAX as u128) as i32;
+
+    // SAFETY: `poll_fd` is a single, valid pollfd, which is what the count of 1 says
+    match unsafe { libc::poll(&mut poll_fd, 1, timeout_ms) } {
+        -1 => {
+            let e = io::Error::last_os_error();
+            if e.kind() == ErrorKind::Interrupted {
+                Ok(false)
+            } else {
+                Err(e.into())
+            }
+        }
+        0 => Ok(false),
+        _ => Ok(true),
+    }
+}
+
+/// A block whose content is pushed by other programs, which write JSON payloads to a named pipe
+/// or a file.
+pub struct FeedBlock {
+    id: String,
+    config: FeedConfig,
+    shown: Option<BlockOutput>,
+
+    /// When the shown payload expires, if it does.
+    expires_at: Option<SystemTime>,
+}
+
+impl FeedBlock {
+    /// Returns a new block with the configuration provided.
+    pub fn new(config: FeedConfig) -> Self {
+        Self {
+            id: String::from("feed"),
+            config,
+            shown: None,
+            expires_at: None,
+        }
+    }
+
+    /// Sets the id of the block, which is used as its name. By default, the id is `feed`.
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = id.to_string();
+        self
+    }
+
+    fn to_update_error(&self, message: String) -> UpdateError {
+        UpdateError {
+            block_name: self.name().to_string(),
+            message,
+        }
+    }
+
+    /// Shows a payload, written at `written_at`. An empty payload hides the block.
+    fn show_payload(&mut self, payload: &str, written_at: SystemTime) -> Result<(), UpdateError> {
+        if payload.trim().is_empty() {
+            self.shown = None;
+            self.expires_at = None;
+            return Ok(());
+        }
+
+        let parsed: Payload = serde_json::from_str(payload)
+            .map_err(|e| self.to_update_error(format!("couldn't parse payload: {}", e)))?;
+
+        let text = match parsed.text {
+            PayloadText::Plain(s) => BlockText::Single(s),
+            PayloadText::Block(t) => t,
+        };
+        let expires_at = match parsed
+            .expire_seconds
+            .or_else(|| self.config.expire_seconds.map(f64::from))
+        {
+            Some(s) => Some(
+                Duration::try_from_secs_f64(s)
+                    .ok()
+                    .and_then(|d| written_at.checked_add(d))
+                    .ok_or_else(|| {
+                        self.to_update_error(String::from(
+                            "couldn't parse payload: `expire_seconds` is out of range",
+                        ))
+                    })?,
+            ),
+            None => None,
+        };
+
+        let output = BlockOutput::new(self.name(), parsed.icon, text, parsed.attention);
+        self.shown = Some(match parsed.animation {
+            Some(a) => output.with_animation(a),
+            None => output,
+        });
+        self.expires_at = expires_at;
+
+        Ok(())
+    }
+
+    /// Hides the block if its payload has expired. Returns true if it did.
+    fn expire(&mut self) -> bool {
+        match self.expires_at {
+            Some(at) if SystemTime::now() >= at => {
+                self.shown = None;
+                self.expires_at = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns how long to wait for payloads before checking again.
+    fn wait_timeout(&self) -> Duration {
+        match self.expires_at {
+            Some(at) => at
+                .duration_since(SystemTime::now())
+                .unwrap_or(Duration::ZERO)
+                .min(POLL_INTERVAL),
+            None => POLL_INTERVAL,
+        }
+    }
+
+    /// Reads payloads until the daemon stops the block, or the status bar goes away.
+    fn feed(
+        &mut self,
+        request_rx: &mpsc::Receiver<BlockRequest>,
+        block_sender: &Sender<BlockOutputMsg>,
+    ) -> Result<(), UpdateError> {
+        let mut source = Source::open(&self.config).map_err(|e| {
+            self.to_update_error(format!("couldn't open {}: {}", self.config.path, e))
+        })?;
+
+        // a file may already have something in it
+        let (content, modified) = source
+            .read_file()
+            .map_err(|e| self.to_update_error(format!("couldn't read: {}", e)))?;
+        if let Err(e) = self.show_payload(&content, modified) {
+            println!("{}", e);
+        }
+        self.expire();
+
+        loop {
+            if block_sender
+                .send(BlockOutputMsg::new(self.name(), self.output()))
+                .is_err()
+            {
+                return Ok(());
+            }
+
+            // payloads only come from the source, so requests are only checked for the daemon
+            // stopping the block
+            loop {
+                if let Err(TryRecvError::Disconnected) = request_rx.try_recv() {
+                    return Ok(());
+                }
+
+                let payloads = source
+                    .wait(self.wait_timeout())
+                    .map_err(|e| self.to_update_error(format!("couldn't read: {}", e)))?;
+
+                let mut changed = self.expire();
+                for (payload, written_at) in payloads {
+                    if let Err(e) = self.show_payload(&payload, written_at) {
+                        println!("{}", e);
+                    }
+                    changed = true;
+                }
+                self.expire();
+
+                if changed {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+impl Block for FeedBlock {
+    fn run(
+        self: Box<Self>,
+        block_sender: Sender<BlockOutputMsg>,
+    ) -> (Vec<JoinHandle<()>>, Sender<BlockRequest>) {
+        let (request_tx, request_rx) = mpsc::channel::<BlockRequest>();
+        let thread_name = format!("{} feed reader", self.name());
+        let mut block = self;
+
+        let handle = thread::Builder::new()
+            .name(thread_name)
+            .spawn(move || {
+                if let Err(e) = block.feed(&request_rx, &block_sender) {
+                    println!("{}", e);
+                    let _ = block_sender.send(BlockOutputMsg::new(block.name(), None));
+                }
+            })
+            .unwrap();
+
+        (vec![handle], request_tx)
+    }
+
+    fn name(&self) -> &str {
+        &self.id
+    }
+
+    fn update(&mut self) -> Result<(), UpdateError> {
+        self.expire();
+        Ok(())
+    }
+
+    fn next_update(&self) -> Option<NextUpdate> {
+        None
+    }
+
+    fn output(&self) -> Option<BlockOutput> {
+        self.shown.clone()
+    }
+}
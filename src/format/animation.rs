@@ -0,0 +1,227 @@
+use super::{
+    blocks::output::BlockText,
+    color::{self, RGBA},
+};
+use crate::utils;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// What's put between the end of scrolling text and its start coming around again.
+const MARQUEE_GAP: &str = "   ";
+
+/// How long fade-ins made with `Animation::fade_in` take.
+const FADE_IN_SECONDS: f32 = 0.5;
+
+/// How long tweens made with `Animation::tween` take.
+const TWEEN_SECONDS: f32 = 0.3;
+
+/// How fast marquees made with `Animation::marquee` scroll.
+const MARQUEE_CHARS_PER_SECOND: f32 = 4.0;
+
+/// An animation that a client plays for a block output by itself, so that the daemon doesn't have
+/// to send every frame. Clients that don't animate show the output as it is, which is the last
+/// frame of animations that end.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub enum Animation {
+    /// Fades the output's colors to the secondary color and back, every `period_seconds`.
+    Pulse {
+        /// How long one pulse takes, in seconds.
+        period_seconds: f32,
+    },
+
+    /// Fades the output in from transparent when it appears.
+    FadeIn {
+        /// How long the fade takes, in seconds.
+        seconds: f32,
+    },
+
+    /// Counts the number `to` in the primary text from `from`, like a volume going from 40% to
+    /// 55%. Progress bars are filled from `from` to their value instead.
+    Tween {
+        /// The number counted from.
+        from: f32,
+
+        /// The number counted to, as it's written (rounded) in the primary text.
+        to: f32,
+
+        /// How long counting takes, in seconds.
+        seconds: f32,
+    },
+
+    /// Scrolls primary text (or a progress bar's label) that's too long to show all at once.
+    Marquee {
+        /// How many characters are shown at a time. Shorter text doesn't scroll.
+        width: usize,
+
+        /// How fast the text scrolls.
+        chars_per_second: f32,
+    },
+}
+
+impl Animation {
+    /// Returns a fade-in of the usual length.
+    pub fn fade_in() -> Self {
+        Self::FadeIn {
+            seconds: FADE_IN_SECONDS,
+        }
+    }
+
+    /// Returns a tween of the usual length, counting from `from` to `to`.
+    pub fn tween(from: f32, to: f32) -> Self {
+        Self::Tween {
+            from,
+            to,
+            seconds: TWEEN_SECONDS,
+        }
+    }
+
+    /// Returns a marquee `width` characters wide, scrolling at the usual speed.
+    pub fn marquee(width: usize) -> Self {
+        Self::Marquee {
+            width,
+            chars_per_second: MARQUEE_CHARS_PER_SECOND,
+        }
+    }
+
+    /// Returns true if the animation should start over when the text it's played with changes
+    /// from `old` to `new`, even though the animation itself hasn't changed. Marquees start over
+    /// for new text, so that it's read from its beginning.
+    pub fn restarts_for(&self, old: &BlockText, new: &BlockText) -> bool {
+        match self {
+            Self::Marquee { .. } => primary_text(old) != primary_text(new),
+            _ => false,
+        }
+    }
+
+    /// Returns true if the animation has more frames to show, `elapsed` after it started.
+    pub fn is_running(&self, text: &BlockText, elapsed: Duration) -> bool {
+        match self {
+            Self::Pulse { .. } => true,
+            Self::FadeIn { seconds } | Self::Tween { seconds, .. } => {
+                elapsed.as_secs_f32() < *seconds
+            }
+            Self::Marquee { width, .. } => primary_text(text).chars().count() > *width,
+        }
+    }
+
+    /// Returns the primary and secondary colors to draw with, `elapsed` after the animation
+    /// started. `colors` are the ones the output has without animating, and `dim` is the color
+    /// pulses fade to.
+    pub fn colors(&self, colors: (RGBA, RGBA), dim: RGBA, elapsed: Duration) -> (RGBA, RGBA) {
+        let (primary, secondary) = colors;
+
+        match self {
+            Self::Pulse { period_seconds } if *period_seconds > 0.0 => {
+                let phase = elapsed.as_secs_f32() % period_seconds / period_seconds;
+                let x = utils::cubic_ease_arc(phase);
+
+                (
+                    color::interpolate_colors(&dim, &primary, x),
+                    color::interpolate_colors(&dim, &secondary, x),
+                )
+            }
+            Self::FadeIn { seconds } => {
+                let x = ease_out(progress(elapsed, *seconds));
+                let transparent = |c: RGBA| RGBA { a: 0, ..c };
+
+                (
+                    color::interpolate_colors(&transparent(primary), &primary, x),
+                    color::interpolate_colors(&transparent(secondary), &secondary, x),
+                )
+            }
+            _ => colors,
+        }
+    }
+
+    /// Returns the text to show, `elapsed` after the animation started.
+    pub fn text(&self, text: &BlockText, elapsed: Duration) -> BlockText {
+        match self {
+            Self::Tween { from, to, seconds } => {
+                let x = ease_out(progress(elapsed, *seconds));
+                let current = from + (to - from) * x;
+
+                if let BlockText::Progress {
+                    min, max, label, ..
+                } = text
+                {
+                    return BlockText::Progress {
+                        value: current,
+                        min: *min,
+                        max: *max,
+                        label: label.clone(),
+                    };
+                }
+
+                map_primary(text, |p| {
+                    p.replacen(
+                        &format!("{}", to.round()),
+                        &format!("{}", current.round()),
+                        1,
+                    )
+                })
+            }
+            Self::Marquee {
+                width,
+                chars_per_second,
+            } => {
+                let offset = (elapsed.as_secs_f32() * chars_per_second) as usize;
+                map_primary(text, |p| marquee(p, *width, offset))
+            }
+            _ => text.clone(),
+        }
+    }
+}
+
+/// Returns how far along an animation lasting `seconds` is, from 0.0 to 1.0.
+fn progress(elapsed: Duration, seconds: f32) -> f32 {
+    if seconds <= 0.0 {
+        1.0
+    } else {
+        (elapsed.as_secs_f32() / seconds).min(1.0)
+    }
+}
+
+/// Eases `x` (from 0.0 to 1.0) so that it starts fast and slows down at the end. This is the first
+/// half of `cubic_ease_arc`, which rises from 0.0 to 1.0.
+fn ease_out(x: f32) -> f32 {
+    utils::cubic_ease_arc(x / 2.0)
+}
+
+/// Returns the text that tweens and marquees change: the primary text, or a progress bar's label.
+fn primary_text(text: &BlockText) -> &str {
+    match text {
+        BlockText::Single(p) | BlockText::Pair(p, _) => p,
+        BlockText::Progress { label, .. } => label.as_deref().unwrap_or_default(),
+    }
+}
+
+fn map_primary(text: &BlockText, f: impl FnOnce(&str) -> String) -> BlockText {
+    match text {
+        BlockText::Single(p) => BlockText::Single(f(p)),
+        BlockText::Pair(p, s) => BlockText::Pair(f(p), s.clone()),
+        BlockText::Progress {
+            value,
+            min,
+            max,
+            label,
+        } => BlockText::Progress {
+            value: *value,
+            min: *min,
+            max: *max,
+            label: label.as_deref().map(f),
+        },
+    }
+}
+
+/// Returns `width` characters of `text`, starting `offset` characters in and wrapping around.
+fn marquee(text: &str, width: usize, offset: usize) -> String {
+    let chars = text.chars().count();
+    if chars <= width {
+        return text.to_string();
+    }
+
+    let looped = text.chars().chain(MARQUEE_GAP.chars()).collect::<Vec<_>>();
+    let start = offset % looped.len();
+
+    looped.iter().cycle().skip(start).take(width).collect()
+}
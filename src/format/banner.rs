@@ -0,0 +1,161 @@
+use super::{
+    blocks::{output::BlockText, BlockOutput},
+    Attention,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// A banner temporarily hides all blocks on the status bar to bring information front and center
+/// for a set duration of time.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Banner {
+    /// A unique identifier, used to update a banner if a twin (with the same id) is sent.
+    id: String,
+
+    /// Banner content.
+    text: String,
+
+    /// How long the banner should remain visible.
+    seconds: f32,
+
+    /// The Attention level of the banner, which may give it a special color.
+    #[serde(default)]
+    attention: Attention,
+
+    /// Whether the banner replaces the whole bar or is shown alongside tertiary blocks.
+    #[serde(default)]
+    placement: BannerPlacement,
+}
+
+/// Where a banner is shown on the status bar.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub enum BannerPlacement {
+    /// The banner temporarily replaces the entire bar.
+    #[default]
+    Large,
+
+    /// The banner is shown alongside tertiary blocks, leaving the rest of the bar alone.
+    Small,
+}
+
+impl Banner {
+    /// Creates a new `Banner` that is shown for `seconds` seconds.
+    pub fn new(id: &str, text: &str, seconds: f32) -> Self {
+        Self {
+            id: id.to_string(),
+            text: text.to_string(),
+            seconds,
+            attention: Attention::Normal,
+            placement: BannerPlacement::Large,
+        }
+    }
+
+    /// Sets the Attention level of the banner.
+    pub fn with_attention(mut self, attention: Attention) -> Self {
+        self.attention = attention;
+        self
+    }
+
+    /// Sets where the banner is shown on the status bar.
+    pub fn with_placement(mut self, placement: BannerPlacement) -> Self {
+        self.placement = placement;
+        self
+    }
+
+    /// Returns the identifier of the banner.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the content of the banner.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns how long the banner should remain visible. Banners too long for a `Duration`
+    /// (including infinite ones) stay until they're replaced.
+    pub fn duration(&self) -> Duration {
+        Duration::try_from_secs_f32(self.seconds.max(0.0)).unwrap_or(Duration::MAX)
+    }
+
+    /// Returns the Attention level of the banner.
+    pub fn attention(&self) -> &Attention {
+        &self.attention
+    }
+
+    /// Returns where the banner is shown on the status bar.
+    pub fn placement(&self) -> BannerPlacement {
+        self.placement
+    }
+
+    /// Returns the banner as if it were a block's output, for showing it alongside other blocks.
+    pub fn to_block_output(&self) -> BlockOutput {
+        BlockOutput::new(
+            "banner",
+            None,
+            BlockText::Single(self.text.clone()),
+            self.attention.clone(),
+        )
+    }
+}
+
+/// A queue of banners. Banners are shown one at a time, in the order they were pushed, each for
+/// its own duration.
+#[derive(Default)]
+pub struct BannerQueue {
+    queue: VecDeque<Banner>,
+
+    /// When the banner at the front of the queue was first shown.
+    shown_since: Option<Instant>,
+}
+
+impl BannerQueue {
+    /// Adds a banner to the end of the queue. If a banner with the same id is already queued, it
+    /// is replaced in place instead. Replacing the banner that's currently shown restarts its
+    /// lifetime.
+    pub fn push(&mut self, banner: Banner) {
+        match self.queue.iter().position(|b| b.id == banner.id) {
+            Some(0) => {
+                self.queue[0] = banner;
+                self.shown_since = Some(Instant::now());
+            }
+            Some(i) => self.queue[i] = banner,
+            None => self.queue.push_back(banner),
+        }
+
+        self.advance();
+    }
+
+    /// Drops banners that have outlived their duration and starts showing the next one.
+    pub fn advance(&mut self) {
+        while let Some(front) = self.queue.front() {
+            match self.shown_since {
+                Some(since) if since.elapsed() >= front.duration() => {
+                    self.queue.pop_front();
+                    self.shown_since = None;
+                }
+                Some(_) => break,
+                None => {
+                    self.shown_since = Some(Instant::now());
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Returns the banner that is currently shown, if any.
+    pub fn current(&self) -> Option<&Banner> {
+        self.shown_since.and(self.queue.front())
+    }
+
+    /// Returns how long the current banner will remain visible, if there is one.
+    pub fn time_left(&self) -> Option<Duration> {
+        let since = self.shown_since?;
+        let front = self.queue.front()?;
+
+        Some(front.duration().saturating_sub(since.elapsed()))
+    }
+}
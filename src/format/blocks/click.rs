@@ -0,0 +1,202 @@
+use crate::errors::*;
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+use std::{fmt, str::FromStr};
+
+/// A mouse button (or scroll direction) that was used on a block. Serialized by name, like
+/// `scroll_up`, or by X11 button number for buttons without a name. Either can be deserialized,
+/// and numbers can be written as numbers too, like `4:` for a click binding in YAML.
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq, Hash)]
+#[serde(into = "String")]
+pub enum MouseButton {
+    /// The left (primary) mouse button.
+    Left,
+
+    /// The middle mouse button, or a click of the scroll wheel.
+    Middle,
+
+    /// The right (secondary) mouse button.
+    Right,
+
+    /// Scrolling up.
+    ScrollUp,
+
+    /// Scrolling down.
+    ScrollDown,
+
+    /// Scrolling left.
+    ScrollLeft,
+
+    /// Scrolling right.
+    ScrollRight,
+
+    /// The "back" side button.
+    Back,
+
+    /// The "forward" side button.
+    Forward,
+
+    /// Any other button, by its X11 button number.
+    Other(u32),
+}
+
+impl MouseButton {
+    /// Returns the button with the X11 button number, as used by the i3bar protocol and lemonbar.
+    pub fn from_x11_code(code: u32) -> Self {
+        match code {
+            1 => Self::Left,
+            2 => Self::Middle,
+            3 => Self::Right,
+            4 => Self::ScrollUp,
+            5 => Self::ScrollDown,
+            6 => Self::ScrollLeft,
+            7 => Self::ScrollRight,
+            8 => Self::Back,
+            9 => Self::Forward,
+            c => Self::Other(c),
+        }
+    }
+
+    /// Returns the X11 button number of this button.
+    pub fn x11_code(&self) -> u32 {
+        match self {
+            Self::Left => 1,
+            Self::Middle => 2,
+            Self::Right => 3,
+            Self::ScrollUp => 4,
+            Self::ScrollDown => 5,
+            Self::ScrollLeft => 6,
+            Self::ScrollRight => 7,
+            Self::Back => 8,
+            Self::Forward => 9,
+            Self::Other(c) => *c,
+        }
+    }
+}
+
+impl FromStr for MouseButton {
+    type Err = MuseStatusError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "left" => Ok(Self::Left),
+            "middle" => Ok(Self::Middle),
+            "right" => Ok(Self::Right),
+            "scroll_up" => Ok(Self::ScrollUp),
+            "scroll_down" => Ok(Self::ScrollDown),
+            "scroll_left" => Ok(Self::ScrollLeft),
+            "scroll_right" => Ok(Self::ScrollRight),
+            "back" => Ok(Self::Back),
+            "forward" => Ok(Self::Forward),
+            _ => match s.parse::<u32>() {
+                Ok(code) => Ok(Self::from_x11_code(code)),
+                Err(_) => Err(MuseStatusError::from(BasicError {
+                    message: format!("this mouse button isn't recognized: `{}`", s),
+                })),
+            },
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MouseButton {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(MouseButtonVisitor)
+    }
+}
+
+/// Reads a `MouseButton` from a name (or a number in a string), or from a number.
+struct MouseButtonVisitor;
+
+impl Visitor<'_> for MouseButtonVisitor {
+    type Value = MouseButton;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "a mouse button name, like `scroll_up`, or an X11 button number"
+        )
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+        s.parse().map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, code: u64) -> Result<Self::Value, E> {
+        u32::try_from(code)
+            .map(MouseButton::from_x11_code)
+            .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(code), &self))
+    }
+
+    fn visit_i64<E: de::Error>(self, code: i64) -> Result<Self::Value, E> {
+        u32::try_from(code)
+            .map(MouseButton::from_x11_code)
+            .map_err(|_| E::invalid_value(de::Unexpected::Signed(code), &self))
+    }
+}
+
+impl From<MouseButton> for String {
+    fn from(button: MouseButton) -> Self {
+        button.to_string()
+    }
+}
+
+impl fmt::Display for MouseButton {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Left => "left",
+            Self::Middle => "middle",
+            Self::Right => "right",
+            Self::ScrollUp => "scroll_up",
+            Self::ScrollDown => "scroll_down",
+            Self::ScrollLeft => "scroll_left",
+            Self::ScrollRight => "scroll_right",
+            Self::Back => "back",
+            Self::Forward => "forward",
+            Self::Other(c) => return write!(f, "{}", c),
+        };
+
+        f.write_str(s)
+    }
+}
+
+/// A click (or scroll) on a block, forwarded to the daemon from a status bar.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ClickEvent {
+    /// The name of the block that was clicked.
+    pub block_name: String,
+
+    /// The button that was used.
+    pub button: MouseButton,
+
+    /// Modifier keys held during the click, like `Shift` or `Mod4`.
+    #[serde(default)]
+    pub modifiers: Vec<String>,
+
+    /// The position of the click on the screen, if the status bar reports it.
+    #[serde(default)]
+    pub position: Option<(i32, i32)>,
+
+    /// The position of the click relative to the top-left corner of the block, if the status bar
+    /// reports it.
+    #[serde(default)]
+    pub relative_position: Option<(i32, i32)>,
+
+    /// The width and height of the block, if the status bar reports them.
+    #[serde(default)]
+    pub size: Option<(i32, i32)>,
+}
+
+impl ClickEvent {
+    /// Creates a new `ClickEvent` without modifiers or coordinates.
+    pub fn new(block_name: &str, button: MouseButton) -> Self {
+        Self {
+            block_name: block_name.to_string(),
+            button,
+            modifiers: Vec::new(),
+            position: None,
+            relative_position: None,
+            size: None,
+        }
+    }
+}
@@ -1,3 +1,6 @@
+/// A module for clicks on blocks.
+pub mod click;
+
 /// A module for block outputs.
 pub mod output;
 
@@ -5,16 +8,28 @@ use crate::{errors::UpdateError, format};
 use chrono::{DateTime, Duration, Local};
 use serde::{Deserialize, Serialize};
 use std::{
-    sync::{
-        mpsc::{self, Sender},
-        Arc, Mutex,
-    },
+    sync::mpsc::{self, RecvTimeoutError, Sender},
     thread::{self, JoinHandle},
     time,
 };
 
+pub use click::{ClickEvent, MouseButton};
 pub use output::BlockOutput;
 
+/// A request sent from the daemon to a running block.
+#[derive(Clone, Debug)]
+pub enum BlockRequest {
+    /// The block should update now (via `muse-status notify <block-name>`).
+    Update,
+
+    /// The block was clicked in a status bar.
+    Click(ClickEvent),
+
+    /// A command for the block, as JSON (like `muse-status timer` sends to timer blocks). What it
+    /// means is up to the block.
+    Command(String),
+}
+
 /// Represents when or in how much time the next update of a block should occur.
 pub enum NextUpdate {
     /// The next update occurs after the specified duration.
@@ -24,6 +39,23 @@ pub enum NextUpdate {
     At(DateTime<Local>),
 }
 
+impl NextUpdate {
+    /// Returns the instant at which the next update should occur. Times that can't be represented
+    /// (like times in the past) fall back to five seconds from now.
+    pub fn instant(&self) -> time::Instant {
+        let chrono_duration = match self {
+            NextUpdate::At(date_time) => *date_time - Local::now(),
+            NextUpdate::In(duration) => *duration,
+        };
+
+        let std_duration = chrono_duration
+            .to_std()
+            .unwrap_or(time::Duration::from_secs(5));
+
+        time::Instant::now() + std_duration
+    }
+}
+
 /// A type to represent the block output that is sent over MPSC channels.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct BlockOutputMsg {
@@ -55,90 +87,114 @@ impl BlockOutputMsg {
     }
 }
 
+/// Runs `block` on a thread of its own, updating it whenever its next update is due and handling
+/// requests in between. This is what `Block::run` does by default, for blocks that override `run`
+/// but sometimes want the usual behavior.
+pub fn run_update_loop<B>(
+    mut block: Box<B>,
+    block_sender: Sender<BlockOutputMsg>,
+) -> (Vec<JoinHandle<()>>, Sender<BlockRequest>)
+where
+    B: Block + ?Sized + 'static,
+{
+    let (request_tx, request_rx) = mpsc::channel::<BlockRequest>();
+    let loop_thread_name = format!("{} update loop", block.name());
+
+    // the block waits for requests until its next update is due, so that one thread can
+    // handle both. the first update happens right away
+    let loop_handle = thread::Builder::new()
+        .name(loop_thread_name)
+        .spawn(move || {
+            let mut next_update_at = Some(time::Instant::now());
+            loop {
+                let request = match next_update_at {
+                    Some(at) => {
+                        let timeout = at.saturating_duration_since(time::Instant::now());
+                        match request_rx.recv_timeout(timeout) {
+                            Ok(r) => Some(r),
+                            Err(RecvTimeoutError::Timeout) => None,
+                            Err(RecvTimeoutError::Disconnected) => break,
+                        }
+                    }
+
+                    // the block doesn't update on its own anymore, so only requests are left
+                    None => match request_rx.recv() {
+                        Ok(r) => Some(r),
+                        Err(_) => break,
+                    },
+                };
+
+                let result = match &request {
+                    None | Some(BlockRequest::Update) => block.update(),
+                    Some(BlockRequest::Click(e)) => block.click(e),
+                    Some(BlockRequest::Command(c)) => block.command(c),
+                };
+                if let Err(e) = result {
+                    println!("{}", e)
+                }
+
+                if block_sender
+                    .send(BlockOutputMsg::new(block.name(), block.output()))
+                    .is_err()
+                {
+                    break;
+                }
+
+                // clicks don't change when the block updates next
+                if !matches!(request, Some(BlockRequest::Click(_))) {
+                    next_update_at = block.next_update().map(|n| n.instant());
+                }
+            }
+        })
+        .unwrap();
+
+    (vec![loop_handle], request_tx)
+}
+
 /// Block is a piece of data in the status bar.
 pub trait Block: Send + Sync {
     /// Runs the block asynchronously. The tuple returns (1) a `Vec` of `JoinHandle`s to any threads
-    /// started asynchronously and (2) a `Sender` that will send notification query to force an
-    /// update on blocks (via `muse-status notify <block-name>`).
+    /// started asynchronously and (2) a `Sender` that will send requests to the block, like
+    /// forcing an update (via `muse-status notify <block-name>`) or reacting to a click.
     ///
-    /// About the returned `Sender`: If a request to notify blocks is sent, the `Sender` sends the
-    /// block name specified (or whatever string is sent through). The `Block`, which should be
-    /// listening with a partnered `Receiver` in a different thread, can handle this data as it
-    /// pleases.
+    /// About the returned `Sender`: The `Block`, which should be listening with a partnered
+    /// `Receiver` in a different thread, can handle each `BlockRequest` as it pleases. When the
+    /// `Sender` is dropped, the block should stop running.
     fn run(
         self: Box<Self>,
         block_sender: Sender<BlockOutputMsg>,
-    ) -> (Vec<JoinHandle<()>>, Sender<()>)
+    ) -> (Vec<JoinHandle<()>>, Sender<BlockRequest>)
     where
         Self: 'static,
     {
-        let (notify_tx, notify_rx) = mpsc::channel::<()>();
-
-        // make arcs and mutexes
-        let loop_thread_name = format!("{} update loop", self.name());
-        let notify_listener_thread_name = format!("{} notify listening thread", self.name());
-        let block_arc_mutex = Arc::new(Mutex::new(self));
-        let arc_clone = block_arc_mutex.clone();
-
-        // clone the sender
-        let output_sender_clone = block_sender.clone();
-
-        // start block auto-updating loop
-        let loop_handle = thread::Builder::new()
-            .name(loop_thread_name)
-            .spawn(move || loop {
-                // update block and return next update
-                let next_update_opt = {
-                    let mut block = block_arc_mutex.lock().unwrap();
-
-                    // update block and then update the bar
-                    if let Err(e) = block.update() {
-                        println!("{}", e)
-                    }
-                    let _ = block_sender.send(BlockOutputMsg::new(block.name(), block.output()));
+        run_update_loop(self, block_sender)
+    }
 
-                    block.next_update()
-                };
+    /// Gives the block a sender it can use to push banners to every subscribed client. Called by
+    /// the daemon before the block is run. Blocks that never show banners can ignore it.
+    fn set_banner_sender(&mut self, _banner_sender: Sender<format::Banner>) {}
 
-                // sleep until next update
-                if let Some(next_update) = next_update_opt {
-                    let chrono_duration = match next_update {
-                        NextUpdate::At(date_time) => {
-                            let now = Local::now();
-                            date_time - now
-                        }
-                        NextUpdate::In(duration) => duration,
-                    };
-
-                    let std_duration = chrono_duration
-                        .to_std()
-                        .unwrap_or(time::Duration::from_secs(5));
-                    thread::sleep(std_duration);
-                } else {
-                    break;
-                }
-            })
-            .unwrap();
-
-        // listen for update requests
-        let notify_listen_handle = thread::Builder::new()
-            .name(notify_listener_thread_name)
-            .spawn(move || {
-                while notify_rx.recv().is_ok() {
-                    let mut block = arc_clone.lock().unwrap();
-                    let _ = block.update();
-                    output_sender_clone
-                        .send(BlockOutputMsg::new(block.name(), block.output()))
-                        .unwrap();
-                }
-            })
-            .unwrap();
+    /// Reacts to a click (or scroll) on the block in a status bar. The block's output is sent
+    /// again afterwards, so any changes made here show up right away. Does nothing by default.
+    fn click(&mut self, _event: &ClickEvent) -> Result<(), UpdateError> {
+        Ok(())
+    }
 
-        (vec![loop_handle, notify_listen_handle], notify_tx)
+    /// Returns the buttons that `click` does something for. Status bars that need an action for
+    /// every button they forward (like lemonbar) only get actions for these, along with buttons
+    /// that have click bindings. None by default.
+    fn click_buttons(&self) -> &'static [MouseButton] {
+        &[]
     }
 
-    /// Sets the banner sender.
-    fn set_banner_sender(&mut self, _banner_sender: Sender<format::Banner>) {}
+    /// Reacts to a command sent to the block, as JSON. The block's output is sent again
+    /// afterwards. Blocks that don't take commands return an error, which they do by default.
+    fn command(&mut self, _command: &str) -> Result<(), UpdateError> {
+        Err(UpdateError {
+            block_name: self.name().to_string(),
+            message: String::from("this block doesn't take commands"),
+        })
+    }
 
     /// Updates the block, returning an error if the update fails.
     fn update(&mut self) -> Result<(), UpdateError>;
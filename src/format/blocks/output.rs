@@ -1,7 +1,47 @@
+use crate::format::animation::Animation;
+use crate::format::blocks::click::MouseButton;
 use crate::format::color::RGBA;
-use crate::format::{Attention, Formatter};
+use crate::format::{Attention, Formatter, Mode};
 use crate::utils;
 use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// The index of the icon font in lemonbar's font list (the second font passed with `-f`).
+const LEMONBAR_ICON_FONT_INDEX: u8 = 2;
+
+/// The command lemonbar outputs when a block is clicked. Pipe lemonbar's output to `sh` to run it.
+const LEMONBAR_CLICK_COMMAND: &str = "muse-status";
+
+/// How many characters wide progress bars are.
+const PROGRESS_BAR_WIDTH: usize = 10;
+
+/// Blocks that fill a progress bar cell by eighths, from one eighth to seven. Full cells use
+/// `PROGRESS_FULL_CELL`.
+const PROGRESS_EIGHTHS: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+/// A full progress bar cell.
+const PROGRESS_FULL_CELL: char = '█';
+
+/// An empty progress bar cell, which shows the rest of the bar's track. It's a different shape
+/// from full cells, so that the bar can be read even when both are the same color.
+const PROGRESS_EMPTY_CELL: char = '░';
+
+/// A filled segment of a progress bar in lemonbar, where bars are drawn in whole segments instead
+/// of eighths.
+const PROGRESS_LEMONBAR_FILLED: char = '━';
+
+/// An empty segment of a progress bar in lemonbar.
+const PROGRESS_LEMONBAR_EMPTY: char = '─';
+
+/// The buttons that are forwarded to blocks from lemonbar, for outputs that don't say which
+/// buttons they react to.
+const LEMONBAR_CLICK_BUTTONS: [MouseButton; 5] = [
+    MouseButton::Left,
+    MouseButton::Middle,
+    MouseButton::Right,
+    MouseButton::ScrollUp,
+    MouseButton::ScrollDown,
+];
 
 /// The output of a Block.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -12,11 +52,29 @@ pub struct BlockOutput {
     /// The icon of the block.
     icon: Option<char>,
 
+    /// Icons shown after `icon`, like one for each of several things a block is showing.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    extra_icons: Vec<char>,
+
     /// The text to show for the block.
     text: BlockText,
 
     /// The Attention level of the output, which may give the block a special color.
     attention: Attention,
+
+    /// An animation for clients to play, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    animation: Option<Animation>,
+
+    /// The buttons that do something when the output is clicked, set by the daemon. `None` if
+    /// the daemon didn't say, as older ones don't.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    buttons: Option<Vec<MouseButton>>,
+
+    /// When the client started playing `animation`. Animations that haven't started are shown as
+    /// they would be without animating.
+    #[serde(skip)]
+    animation_started: Option<Instant>,
 }
 
 impl BlockOutput {
@@ -30,11 +88,51 @@ impl BlockOutput {
         Self {
             block_name: block_name.to_string(),
             icon,
+            extra_icons: Vec::new(),
             text,
             attention,
+            animation: None,
+            buttons: None,
+            animation_started: None,
         }
     }
 
+    /// Sets icons to show after the output's icon. They're only shown if the output has an icon.
+    pub fn with_extra_icons(mut self, icons: Vec<char>) -> Self {
+        self.extra_icons = icons;
+        self
+    }
+
+    /// Sets an animation for clients to play with this output.
+    pub fn with_animation(mut self, animation: Animation) -> Self {
+        self.animation = Some(animation);
+        self
+    }
+
+    /// Sets the buttons that do something when the output is clicked.
+    pub fn with_buttons(mut self, buttons: Vec<MouseButton>) -> Self {
+        self.buttons = Some(buttons);
+        self
+    }
+
+    /// Removes the output's animation, for clients that can't play animations.
+    pub fn without_animation(mut self) -> Self {
+        self.animation = None;
+        self.animation_started = None;
+        self
+    }
+
+    /// Replaces a `Progress` text with its percentage, for clients that can't draw progress bars.
+    pub fn without_progress(mut self) -> Self {
+        self.text = self.text.without_progress();
+        self
+    }
+
+    /// Returns true if the output has an animation.
+    pub fn has_animation(&self) -> bool {
+        self.animation.is_some()
+    }
+
     /// Returns the name of the block this output is from.
     pub fn name(&self) -> String {
         self.block_name.to_owned()
@@ -45,15 +143,129 @@ impl BlockOutput {
         &self.text
     }
 
+    /// Returns the attention level of the output.
+    pub fn attention(&self) -> &Attention {
+        &self.attention
+    }
+
+    /// Starts playing the output's animation, if it has one. `previous` is the output this one
+    /// replaces: if it had the same animation, the animation carries on where it was instead of
+    /// starting over, so that updates don't restart pulses or fade blocks in again.
+    pub fn start_animation(&mut self, previous: Option<&BlockOutput>) {
+        let animation = match &self.animation {
+            Some(a) => a,
+            None => return,
+        };
+
+        self.animation_started = match previous {
+            Some(p)
+                if p.animation.as_ref() == Some(animation)
+                    && !animation.restarts_for(&p.text, &self.text) =>
+            {
+                p.animation_started.or_else(|| Some(Instant::now()))
+            }
+            _ => Some(Instant::now()),
+        };
+    }
+
+    /// Returns true if the output changes over time, either because its attention pulses or
+    /// because its animation is still playing.
+    pub fn is_animating(&self) -> bool {
+        if self.attention.is_pulse() {
+            return true;
+        }
+
+        match (&self.animation, self.animation_started) {
+            (Some(a), Some(started)) => a.is_running(&self.text, started.elapsed()),
+            _ => false,
+        }
+    }
+
+    /// Returns the text, the primary and secondary colors, and the icon color to draw the output
+    /// with right now.
+    fn frame(&self, f: &Formatter) -> (BlockText, (RGBA, RGBA), RGBA) {
+        let colors = f.block_colors(&self.block_name, &self.attention);
+        let icon_color = f
+            .block_icon_color(&self.block_name, &self.attention)
+            .unwrap_or(colors.0);
+
+        match (&self.animation, self.animation_started) {
+            (Some(a), Some(started)) if f.frame_rate > 0.0 => {
+                let elapsed = started.elapsed();
+                (
+                    a.text(&self.text, elapsed),
+                    a.colors(colors, f.theme.secondary, elapsed),
+                    a.colors((icon_color, colors.1), f.theme.secondary, elapsed)
+                        .0,
+                )
+            }
+            _ => (self.text.clone(), colors, icon_color),
+        }
+    }
+
+    /// Returns the icon to draw, followed by any extra icons. The block's style may replace them.
+    fn icon(&self, f: &Formatter) -> Option<String> {
+        let style = f.block_style(&self.block_name);
+        let icon = self.icon?;
+
+        let icons = std::iter::once(icon)
+            .chain(self.extra_icons.iter().copied())
+            .map(|i| match style {
+                Some(s) => s.icon(i),
+                None => i.to_string(),
+            })
+            .collect::<Vec<_>>();
+
+        Some(icons.join(" "))
+    }
+
+    /// Formats the output for lemonbar, including the icon (in the icon font) and the full text.
+    /// Clicking or scrolling on the output forwards the click to the block through the daemon.
+    pub fn as_lemonbar_string(&self, f: &Formatter) -> String {
+        let (text, (primary_color, secondary_color), icon_color) = self.frame(f);
+        let text = text.to_lemonbar_string(primary_color, secondary_color);
+        let content = match self.icon(f) {
+            Some(i) => format!(
+                "{}  {}",
+                utils::make_lemonbar_string(&i, Some(icon_color), Some(LEMONBAR_ICON_FONT_INDEX)),
+                text
+            ),
+            None => text,
+        };
+        let content = match f.block_background(&self.block_name) {
+            Some(c) => format!("%{{B#{}}}{}%{{B-}}", c.hex_string(Mode::Lemonbar), content),
+            None => content,
+        };
+
+        // nest an action for every button the block reacts to around the content, since lemonbar
+        // only allows so many actions at once. the command is run by a shell, so the name is
+        // quoted for it as well as escaped for lemonbar
+        let block_name = utils::shell_quote(&self.block_name).replace(':', "\\:");
+        self.buttons
+            .as_deref()
+            .unwrap_or(&LEMONBAR_CLICK_BUTTONS)
+            .iter()
+            .fold(content, |content, button| {
+                format!(
+                    "%{{A{}:{} click {} {}:}}{}%{{A}}",
+                    button.x11_code(),
+                    LEMONBAR_CLICK_COMMAND,
+                    block_name,
+                    button,
+                    content
+                )
+            })
+    }
+
     /// Formats the output as a pango string. The first string returned is the full text including
     /// icon, primary text, and secondary text. The second string is the same but excludes the
     /// secondary text.
     pub fn as_pango_strings(&self, f: &Formatter) -> (String, Option<String>) {
-        let (primary_color, secondary_color) = self.attention.colors(f);
-        let icon_pango = self.icon.map(|i| {
-            utils::make_pango_string(&i.to_string(), Some(primary_color), Some(&f.icon_font))
-        });
-        let (full, short_opt) = self.text.to_pango_strings(primary_color, secondary_color);
+        let (text, (primary_color, secondary_color), icon_color) = self.frame(f);
+        let icon_pango = self
+            .icon(f)
+            .map(|i| utils::make_pango_string(&i, Some(icon_color), Some(&f.theme.icon_font)));
+        let (full, short_opt) = text.to_pango_strings(primary_color, secondary_color);
         if let Some(icon) = icon_pango {
             // strings with icons
             let full_with_icon = format!("{icon}  {full}");
@@ -87,6 +299,22 @@ pub enum BlockText {
 
     /// Two strings of text, primary and secondary.
     Pair(String, String),
+
+    /// A progress bar (or slider), filled to show where `value` is between `min` and `max`. The
+    /// bar is primary-colored, and the label (if any) is secondary-colored.
+    Progress {
+        /// How far along the bar is.
+        value: f32,
+
+        /// The value of an empty bar.
+        min: f32,
+
+        /// The value of a full bar.
+        max: f32,
+
+        /// Text shown after the bar.
+        label: Option<String>,
+    },
 }
 
 impl BlockText {
@@ -97,6 +325,9 @@ impl BlockText {
     /// If `Pair`, the long version is both strings, and the short version is only the primary
     /// string.
     ///
+    /// If `Progress`, the long version is the bar and its label, and the short version is only the
+    /// bar. Without a label, the long version is the bar and the short version is `None`.
+    ///
     /// TODO: Add `to_pango_strings_with_icon` to include an icon with the primary text and reduce
     /// the amount of markup we have to do
     fn to_pango_strings(
@@ -109,13 +340,19 @@ impl BlockText {
             BlockText::Single(s) | BlockText::Pair(_, s) => {
                 let color = match self {
                     BlockText::Single(_) => primary_color,
-                    BlockText::Pair(_, _) => secondary_color,
+                    _ => secondary_color,
                 };
                 utils::make_pango_string(s, Some(color), None)
             }
+            BlockText::Progress { label: Some(l), .. } => {
+                utils::make_pango_string(l, Some(secondary_color), None)
+            }
+            BlockText::Progress { label: None, .. } => {
+                self.progress_bar_pango_string(primary_color, secondary_color)
+            }
         };
 
-        let short = self.to_short_pango_string(primary_color);
+        let short = self.to_short_pango_string(primary_color, secondary_color);
         let long = if let Some(ref short_str) = short {
             format!("{}  {}", short_str, second_half)
         } else {
@@ -125,15 +362,111 @@ impl BlockText {
         (long, short)
     }
 
+    /// Returns the lemonbar representation of this `BlockText`, with the secondary text (if any)
+    /// colored differently to accent the primary text.
+    fn to_lemonbar_string(&self, primary_color: RGBA, secondary_color: RGBA) -> String {
+        match self {
+            BlockText::Single(s) => utils::make_lemonbar_string(s, Some(primary_color), None),
+            BlockText::Pair(p, s) => format!(
+                "{}  {}",
+                utils::make_lemonbar_string(p, Some(primary_color), None),
+                utils::make_lemonbar_string(s, Some(secondary_color), None)
+            ),
+            BlockText::Progress { label, .. } => {
+                // lemonbar can't be relied on to have fonts with eighth blocks, so the bar is made
+                // of line segments instead
+                let filled = (self.progress() * PROGRESS_BAR_WIDTH as f32).round() as usize;
+                let bar = progress_bar_parts(
+                    &PROGRESS_LEMONBAR_FILLED.to_string().repeat(filled),
+                    &PROGRESS_LEMONBAR_EMPTY
+                        .to_string()
+                        .repeat(PROGRESS_BAR_WIDTH - filled),
+                    |s, c| utils::make_lemonbar_string(s, Some(c), None),
+                    primary_color,
+                    secondary_color,
+                );
+
+                match label {
+                    Some(l) => format!(
+                        "{}  {}",
+                        bar,
+                        utils::make_lemonbar_string(l, Some(secondary_color), None)
+                    ),
+                    None => bar,
+                }
+            }
+        }
+    }
+
+    /// Returns a `Progress` text as its percentage, like `Single("40%")`, with its label (if any)
+    /// as secondary text. Other kinds of text are returned as they are.
+    pub fn without_progress(self) -> Self {
+        let percentage = format!("{:.0}%", self.progress() * 100.0);
+        match self {
+            BlockText::Progress { label: Some(l), .. } => BlockText::Pair(percentage, l),
+            BlockText::Progress { label: None, .. } => BlockText::Single(percentage),
+            text => text,
+        }
+    }
+
+    /// Returns true if the text is a `Progress` bar.
+    pub fn is_progress(&self) -> bool {
+        matches!(self, BlockText::Progress { .. })
+    }
+
+    /// Returns how full a `Progress` bar is, from 0.0 to 1.0. Other kinds of text are empty.
+    fn progress(&self) -> f32 {
+        match self {
+            BlockText::Progress {
+                value, min, max, ..
+            } => {
+                let progress = (value - min) / (max - min);
+                if progress.is_finite() {
+                    progress.clamp(0.0, 1.0)
+                } else {
+                    0.0
+                }
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Returns a `Progress` bar in pango markup, drawn with eighth blocks. The filled part of the
+    /// bar is primary-colored and its track is secondary-colored.
+    fn progress_bar_pango_string(&self, primary_color: RGBA, secondary_color: RGBA) -> String {
+        let eighths = (self.progress() * (PROGRESS_BAR_WIDTH * 8) as f32).round() as usize;
+        let (full_cells, partial_eighths) = (eighths / 8, eighths % 8);
+
+        let mut filled = PROGRESS_FULL_CELL.to_string().repeat(full_cells);
+        let mut empty_cells = PROGRESS_BAR_WIDTH - full_cells;
+        if partial_eighths > 0 {
+            filled.push(PROGRESS_EIGHTHS[partial_eighths - 1]);
+            empty_cells -= 1;
+        }
+
+        progress_bar_parts(
+            &filled,
+            &PROGRESS_EMPTY_CELL.to_string().repeat(empty_cells),
+            |s, c| utils::make_pango_string(s, Some(c), None),
+            primary_color,
+            secondary_color,
+        )
+    }
+
     /// Returns the short version of the pango markup representation of this `BlockText`.
     ///
     /// If `Single`, the short version is `None`.
     ///
     /// If `Pair`, the short version is only the primary string.
-    fn to_short_pango_string(&self, primary_color: RGBA) -> Option<String> {
+    ///
+    /// If `Progress`, the short version is only the bar if there's a label, and `None` otherwise.
+    fn to_short_pango_string(&self, primary_color: RGBA, secondary_color: RGBA) -> Option<String> {
         match self {
-            BlockText::Single(_) => None,
+            BlockText::Single(_) | BlockText::Progress { label: None, .. } => None,
             BlockText::Pair(p, _) => Some(utils::make_pango_string(p, Some(primary_color), None)),
+            BlockText::Progress { label: Some(_), .. } => {
+                Some(self.progress_bar_pango_string(primary_color, secondary_color))
+            }
         }
     }
 
@@ -162,3 +495,19 @@ impl BlockText {
         self.to_pango_strings(primary_color, secondary_color).0
     }
 }
+
+/// Joins the filled part of a progress bar, colored with `filled_color`, to its empty part, colored
+/// with `empty_color`. Parts that are empty are left out, instead of being colored for nothing.
+fn progress_bar_parts(
+    filled: &str,
+    empty: &str,
+    colorize: impl Fn(&str, RGBA) -> String,
+    filled_color: RGBA,
+    empty_color: RGBA,
+) -> String {
+    [(filled, filled_color), (empty, empty_color)]
+        .iter()
+        .filter(|(s, _)| !s.is_empty())
+        .map(|(s, c)| colorize(s, *c))
+        .collect()
+}
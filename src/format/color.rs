@@ -1,5 +1,5 @@
 use super::Mode;
-use serde::{Deserialize, Serialize};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
 use std::str::FromStr;
 
@@ -24,7 +24,7 @@ pub enum Color {
 }
 
 /// Represents an RGBA color using bytes, each 0 - 255.
-#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
 pub struct RGBA {
     /// Red.
     pub r: u8,
@@ -138,6 +138,32 @@ pub fn interpolate_colors(first: &RGBA, second: &RGBA, interpolation: f32) -> RG
     }
 }
 
+/// Deserializes a color written like `#rrggbb` or `#rrggbbaa`, for config and theme files.
+pub(crate) fn deserialize_color<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<RGBA, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    RGBA::from_str(&s).map_err(D::Error::custom)
+}
+
+/// Like `deserialize_color`, for colors that don't have to be set.
+pub(crate) fn deserialize_optional_color<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<RGBA>, D::Error> {
+    deserialize_color(deserializer).map(Some)
+}
+
+/// Serializes a color like `#rrggbbaa`, so that `deserialize_optional_color` can read it back.
+pub(crate) fn serialize_optional_color<S: Serializer>(
+    color: &Option<RGBA>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match color {
+        Some(c) => serializer.serialize_str(&format!("#{}", c.hex_string(Mode::JsonProtocol))),
+        None => serializer.serialize_none(),
+    }
+}
+
 /// Returned when muse-status has an issue parsing an RGBA struct from a hex string, e.g. rrggbbaa
 #[derive(Debug)]
 pub enum RGBAParseError {
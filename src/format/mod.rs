@@ -1,31 +1,48 @@
+/// The module for animations.
+pub mod animation;
+
+/// The module for banners.
+pub mod banner;
+
 /// The module for all things blocks.
 pub mod blocks;
 
 /// The module for all things colors.
 pub mod color;
 
-// TODO create a separate module for Banner
+/// The module for block styles.
+pub mod style;
+
+/// The module for themes.
+pub mod theme;
 
 use crate::daemon::DataPayload;
 use crate::errors::{BasicError, MuseStatusError};
 use crate::format::blocks::output::BlockOutput;
 use crate::utils;
+use banner::{BannerPlacement, BannerQueue};
 use color::{Color, RGBA};
 use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
+use std::collections::HashMap;
 use std::str::FromStr;
+use std::time::Duration;
+use style::{Align, BlockStyle, MinWidth};
+use theme::Theme;
+
+pub use banner::Banner;
 
 /// Eight spaces.
 const MARKUP_SEPARATOR: &str = "        ";
 
 /// Attention provides a way to easily apply colors to a Block, without actually passing any RGBA
 /// values.
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
 pub enum Attention {
     /// Static dim color.
     Dim,
 
     /// Default static color.
+    #[default]
     Normal,
 
     /// Static warning color.
@@ -46,24 +63,42 @@ impl Attention {
     /// primary and secondary colors, respectively.
     pub fn colors(&self, f: &Formatter) -> (RGBA, RGBA) {
         match self {
-            Self::Normal => (f.primary_color, f.secondary_color),
-            Self::Dim => (f.secondary_color, f.secondary_color),
-            Self::Warning => (f.warning_color, f.warning_color),
-            Self::Alarm => (f.alarm_color, f.alarm_color),
+            Self::Normal => (f.theme.primary, f.theme.secondary),
+            Self::Dim => (f.theme.secondary, f.theme.secondary),
+            Self::Warning => (f.theme.warning, f.theme.warning),
+            Self::Alarm => (f.theme.alarm, f.theme.alarm),
             Self::WarningPulse => {
-                // TODO
-                // let c = f.get_warn_pulse_color();
-                // (c, c)
-                (f.warning_color, f.warning_color)
+                let c = f.get_warn_pulse_color();
+                (c, c)
             }
             Self::AlarmPulse => {
-                // TODO
-                // let c = f.get_alarm_pulse_color();
-                // (c, c)
-                (f.alarm_color, f.alarm_color)
+                let c = f.get_alarm_pulse_color();
+                (c, c)
             }
         }
     }
+
+    /// Returns true if this `Attention` pulses, meaning that its colors change over time.
+    pub fn is_pulse(&self) -> bool {
+        matches!(self, Self::WarningPulse | Self::AlarmPulse)
+    }
+}
+
+impl FromStr for Attention {
+    type Err = MuseStatusError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dim" => Ok(Self::Dim),
+            "normal" => Ok(Self::Normal),
+            "warning" => Ok(Self::Warning),
+            "warning-pulse" => Ok(Self::WarningPulse),
+            "alarm" => Ok(Self::Alarm),
+            "alarm-pulse" => Ok(Self::AlarmPulse),
+            _ => Err(MuseStatusError::from(BasicError {
+                message: format!("this attention level isn't recognized: `{}`", s),
+            })),
+        }
+    }
 }
 
 /// For different types of status modes, for different status bars that parse information
@@ -104,62 +139,28 @@ impl FromStr for Mode {
 /// a format that can be read by status bars (and you!).
 pub struct Formatter {
     formatting_mode: Mode,
-    primary_color: RGBA,
-    secondary_color: RGBA,
-    alarm_color: RGBA,
-    warning_color: RGBA,
-    icon_font: String,
+    theme: Theme,
 
-    /// A banner queue.
-    #[allow(dead_code)]
-    banners: VecDeque<Banner>,
-}
-
-/// A banner temporarily hides all blocks on the status bar to bring information front and center
-/// for a set duration of time.
-#[allow(dead_code)]
-pub struct Banner {
-    /// A unique identifier, used to update a banner if a twin (with the same id) is sent.
-    id: String,
+    /// Styles for specific blocks, by block name.
+    styles: HashMap<String, BlockStyle>,
 
-    /// Banner content.
-    text: String,
+    /// How many frames per second pulses and animations are redrawn at. If zero, pulses are shown
+    /// with static colors and animations aren't played.
+    frame_rate: f32,
 
-    /// How long the banner should remain visible.
-    seconds: f32,
+    /// A banner queue.
+    banners: BannerQueue,
 }
 
 impl Default for Formatter {
     fn default() -> Self {
         Self {
             formatting_mode: Mode::JsonProtocol,
-            primary_color: RGBA {
-                r: 0xff,
-                g: 0xff,
-                b: 0xff,
-                a: 0xff,
-            },
-            secondary_color: RGBA {
-                r: 0xc0,
-                g: 0xc0,
-                b: 0xc0,
-                a: 0xff,
-            },
-            alarm_color: RGBA {
-                r: 0xff,
-                g: 0x00,
-                b: 0x00,
-                a: 0xff,
-            },
-            warning_color: RGBA {
-                r: 0xff,
-                g: 0xaa,
-                b: 0x00,
-                a: 0xff,
-            },
-            icon_font: String::from("Material Design Icons 12"),
+            theme: Theme::default(),
+            styles: HashMap::new(),
+            frame_rate: 15.0,
 
-            banners: VecDeque::new(),
+            banners: BannerQueue::default(),
         }
     }
 }
@@ -170,35 +171,19 @@ impl Formatter {
         Default::default()
     }
 
-    /// Creates and returns a new Formatter from command line arguments.
-    pub fn from_env() -> Result<Self, MuseStatusError> {
-        let mut args = std::env::args().skip(1);
-
-        let mut formatter: Self = Default::default();
-
-        while let Some(arg) = args.next() {
-            if let Some(value) = args.next() {
-                match arg.as_str() {
-                    "-p" | "--primary-color" => formatter.set_primary_color(&value)?,
-                    "-s" | "--secondary-color" => formatter.set_secondary_color(&value)?,
-                    "-i" | "--icon-font" => formatter.set_icon_font(&value),
-                    "-m" | "--mode" => formatter.set_format_mode(value.parse()?),
-                    _ => (),
-                }
-            } else {
-                return Err(MuseStatusError::from(BasicError {
-                    message: format!("flag requires a value: {}", arg),
-                }));
-            }
-        }
-
-        Ok(formatter)
-    }
-
     /// Chains status bites together, ensuring that there are no awkward spaces between bites, and
     /// outputs a result fit to be parsed by a status bar. The string can safely be printed as-is
     /// without additional formatting or newlines.
-    pub fn format_data(&self, data: DataPayload) -> String {
+    pub fn format_data(&self, mut data: DataPayload) -> String {
+        // a large banner takes the place of the whole bar, while a small banner is shown
+        // alongside tertiary blocks
+        if let Some(banner) = self.banners.current() {
+            match banner.placement() {
+                BannerPlacement::Large => return self.format_banner(banner),
+                BannerPlacement::Small => data.push_tertiary(banner.to_block_output()),
+            }
+        }
+
         match self.formatting_mode {
             Mode::JsonProtocol => {
                 let mut json_strings = Vec::new();
@@ -240,7 +225,21 @@ impl Formatter {
                 let joined = json_strings.join(",");
                 format!(",[{}]", joined)
             }
-            Mode::Lemonbar => unimplemented!(),
+            Mode::Lemonbar => match data {
+                // primary blocks are aligned left, secondary blocks in the center, and tertiary
+                // blocks to the right
+                DataPayload::Ranked {
+                    primary,
+                    secondary,
+                    tertiary,
+                } => format!(
+                    "%{{l}}{}%{{c}}{}%{{r}}{}",
+                    self.join_as_lemonbar(&primary),
+                    self.join_as_lemonbar(&secondary),
+                    self.join_as_lemonbar(&tertiary)
+                ),
+                DataPayload::Unranked(outputs) => self.join_as_lemonbar(&outputs),
+            },
             Mode::Markup => {
                 let mut markup_strings = Vec::new();
                 match data {
@@ -291,24 +290,172 @@ impl Formatter {
         &self.formatting_mode
     }
 
-    /// Activates and displays a banner.
-    pub fn banner(&mut self, _banner: Banner) {
-        unimplemented!()
+    /// Activates and displays a banner. If another banner is already being displayed, the new
+    /// banner is queued (or replaces its twin with the same id).
+    pub fn banner(&mut self, banner: Banner) {
+        self.banners.push(banner)
+    }
+
+    /// Drops any banners that have expired. Should be called before formatting data.
+    pub fn update_banners(&mut self) {
+        self.banners.advance()
+    }
+
+    /// Returns how long until the output needs to be redrawn, even if no new data arrives. If
+    /// `None`, the output only needs to be redrawn when data changes. `animating` should be true
+    /// if the last output had anything pulsing or animating in it (see `is_animating`), so that it
+    /// keeps moving.
+    pub fn next_redraw(&self, animating: bool) -> Option<Duration> {
+        let next_frame = if animating && self.frame_rate > 0.0 {
+            Some(Duration::from_secs_f32(1.0 / self.frame_rate))
+        } else {
+            None
+        };
+
+        match (self.banners.time_left(), next_frame) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Returns true if anything that would be shown for `data` pulses or is animating, including
+    /// the current banner.
+    pub fn is_animating(&self, data: &DataPayload) -> bool {
+        if let Some(banner) = self.banners.current() {
+            if banner.attention().is_pulse() {
+                return true;
+            }
+
+            // a large banner hides everything else
+            if banner.placement() == BannerPlacement::Large {
+                return false;
+            }
+        }
+
+        data.outputs().iter().any(|o| o.is_animating())
+    }
+
+    /// Formats a banner in place of all block outputs.
+    fn format_banner(&self, banner: &Banner) -> String {
+        let (color, _) = banner.attention().colors(self);
+        match self.formatting_mode {
+            Mode::JsonProtocol => {
+                let full_text = utils::make_pango_string(banner.text(), Some(color), None);
+                let json = JsonBlock {
+                    name: String::from("banner"),
+                    short_text: full_text.clone(),
+                    full_text,
+                    separator: false,
+                    min_width: None,
+                    align: None,
+                    markup: String::from("pango"),
+                    background: self
+                        .theme
+                        .background
+                        .map(|c| format!("#{}", c.hex_string(Mode::JsonProtocol))),
+                };
+
+                format!(",[{}]", serde_json::to_string(&json).unwrap_or_default())
+            }
+            Mode::Lemonbar => format!(
+                "%{{c}}%{{F#{}}}{}%{{F-}}",
+                color.hex_string(Mode::Lemonbar),
+                utils::lemonbar_escape(banner.text())
+            ),
+            Mode::Markup => utils::make_pango_string(banner.text(), Some(color), None),
+        }
+    }
+
+    /// Sets the theme of the Formatter, replacing its colors and icon font.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    /// Sets the styles of specific blocks, by block name. Blocks without a style are drawn with
+    /// the default one.
+    pub fn set_styles(&mut self, styles: HashMap<String, BlockStyle>) {
+        self.styles = styles;
     }
 
     /// Sets the icon font of the Formatter.
     pub fn set_icon_font(&mut self, font: &str) {
-        self.icon_font = font.to_owned();
+        self.theme.icon_font = font.to_owned();
     }
 
     /// Sets the primary color of the Formatting
     pub fn set_primary_color(&mut self, color: &str) -> Result<(), color::RGBAParseError> {
-        Self::set_color(&mut self.primary_color, color)
+        Self::set_color(&mut self.theme.primary, color)
     }
 
     /// Sets the secondary (dim) color of the Formatting
     pub fn set_secondary_color(&mut self, color: &str) -> Result<(), color::RGBAParseError> {
-        Self::set_color(&mut self.secondary_color, color)
+        Self::set_color(&mut self.theme.secondary, color)
+    }
+
+    /// Sets the warning color of the Formatter.
+    pub fn set_warning_color(&mut self, color: &str) -> Result<(), color::RGBAParseError> {
+        Self::set_color(&mut self.theme.warning, color)
+    }
+
+    /// Sets the alarm color of the Formatter.
+    pub fn set_alarm_color(&mut self, color: &str) -> Result<(), color::RGBAParseError> {
+        Self::set_color(&mut self.theme.alarm, color)
+    }
+
+    /// Returns the primary and secondary colors of an output from the block named `block_name`,
+    /// with the given attention. The theme may have colors for that block; warnings and alarms
+    /// always use the theme's warning and alarm colors.
+    pub(crate) fn block_colors(&self, block_name: &str, attention: &Attention) -> (RGBA, RGBA) {
+        let overrides = match self.theme.blocks.get(block_name) {
+            Some(b) => b,
+            None => return attention.colors(self),
+        };
+        let primary = overrides.primary.unwrap_or(self.theme.primary);
+        let secondary = overrides.secondary.unwrap_or(self.theme.secondary);
+
+        match attention {
+            Attention::Normal => (primary, secondary),
+            Attention::Dim => (secondary, secondary),
+            _ => attention.colors(self),
+        }
+    }
+
+    /// Returns the background of outputs from the block named `block_name`, if the theme sets one.
+    pub(crate) fn block_background(&self, block_name: &str) -> Option<RGBA> {
+        self.theme
+            .blocks
+            .get(block_name)
+            .and_then(|b| b.background)
+            .or(self.theme.background)
+    }
+
+    /// Returns the style of the block named `block_name`, if it has one.
+    pub(crate) fn block_style(&self, block_name: &str) -> Option<&BlockStyle> {
+        self.styles.get(block_name)
+    }
+
+    /// Returns the color of the icon of an output from the block named `block_name`, with the
+    /// given attention, if the block's style gives its icons a color of their own. Only normal
+    /// outputs use it, so that dim, warning and alarm icons still match their text.
+    pub(crate) fn block_icon_color(&self, block_name: &str, attention: &Attention) -> Option<RGBA> {
+        match attention {
+            Attention::Normal => self.block_style(block_name).and_then(|s| s.icon_color),
+            _ => None,
+        }
+    }
+
+    /// Sets how many frames per second pulses and animations are redrawn at. Zero turns them off.
+    pub fn set_frame_rate(&mut self, fps: &str) -> Result<(), MuseStatusError> {
+        match fps.parse::<f32>() {
+            Ok(f) if f.is_finite() && f >= 0.0 => {
+                self.frame_rate = f;
+                Ok(())
+            }
+            _ => Err(MuseStatusError::from(format!(
+                "`{}` isn't a valid frame rate",
+                fps
+            ))),
+        }
     }
 
     fn set_color(c: &mut RGBA, s: &str) -> Result<(), color::RGBAParseError> {
@@ -320,6 +467,11 @@ impl Formatter {
     fn get_pulse_color(&self, color: &RGBA, seconds: f32) -> RGBA {
         use std::time::Duration;
 
+        // without redraws, a pulse would be stuck wherever it was drawn
+        if self.frame_rate <= 0.0 {
+            return *color;
+        }
+
         let now = chrono::Local::now();
 
         // get alpha byte value. interpolation is a value from
@@ -328,28 +480,28 @@ impl Formatter {
         let unix_millis: u128 = (now.timestamp_nanos() as u128
             / Duration::from_millis(1).as_nanos())
             % max_millis as u128;
-        let interpolation = utils::cubic_ease_arc((unix_millis / max_millis as u128) as f32);
+        let interpolation = utils::cubic_ease_arc(unix_millis as f32 / max_millis as f32);
 
-        color::interpolate_colors(&self.secondary_color, color, interpolation)
+        color::interpolate_colors(&self.theme.secondary, color, interpolation)
     }
 
     /// A convenience method for giving a standard, pulsing alarm color.
     pub fn get_alarm_pulse_color(&self) -> RGBA {
-        self.get_pulse_color(&self.alarm_color, 1.0)
+        self.get_pulse_color(&self.theme.alarm, 1.0)
     }
 
     /// A convenience method for giving a standard, pulsing warning color.
     pub fn get_warn_pulse_color(&self) -> RGBA {
-        self.get_pulse_color(&self.warning_color, 2.0)
+        self.get_pulse_color(&self.theme.warning, 2.0)
     }
 
     #[allow(dead_code)]
     fn color_to_rgba(&self, c: &Color) -> RGBA {
         match c {
-            Color::Alarm => self.alarm_color,
-            Color::Warning => self.warning_color,
-            Color::Primary => self.primary_color,
-            Color::Secondary => self.secondary_color,
+            Color::Alarm => self.theme.alarm,
+            Color::Warning => self.theme.warning,
+            Color::Primary => self.theme.primary,
+            Color::Secondary => self.theme.secondary,
             Color::Other(rgba) => *rgba,
         }
     }
@@ -357,14 +509,20 @@ impl Formatter {
     /// Formats the BlockOutput for the i3 JSON protocol. None if body is None.
     fn block_output_as_json_protocol_string(&self, block_output: &BlockOutput) -> Option<String> {
         let (full_text, short_text) = block_output.as_pango_strings(self);
+        let style = self.block_style(&block_output.name());
 
         let json = JsonBlock {
             full_text,
             short_text: short_text.unwrap_or_default(), // TODO: Make short_text optional.
                                                         // It is not required as part of
                                                         // the JSON protocol for sway.
-            separator: true,
+            separator: style.is_none_or(|s| s.separator),
+            min_width: style.and_then(|s| s.min_width.clone()),
+            align: style.and_then(|s| s.align),
             markup: String::from("pango"),
+            background: self
+                .block_background(&block_output.name())
+                .map(|c| format!("#{}", c.hex_string(Mode::JsonProtocol))),
             name: block_output.name(),
         };
 
@@ -374,10 +532,28 @@ impl Formatter {
         }
     }
 
+    /// Formats the BlockOutputs for lemonbar, separated by spaces.
+    fn join_as_lemonbar(&self, block_outputs: &[BlockOutput]) -> String {
+        block_outputs
+            .iter()
+            .map(|o| o.as_lemonbar_string(self))
+            .collect::<Vec<String>>()
+            .join(MARKUP_SEPARATOR)
+    }
+
     /// Formats the BlockOutput for plain markup output.
     fn block_output_as_markup(&self, block_output: &BlockOutput) -> String {
         // return only the long format
-        block_output.as_pango_strings(self).0
+        let markup = block_output.as_pango_strings(self).0;
+
+        match self.block_background(&block_output.name()) {
+            Some(c) => format!(
+                "<span background='#{}'>{}</span>",
+                c.hex_string(Mode::JsonProtocol),
+                markup
+            ),
+            None => markup,
+        }
     }
 }
 
@@ -387,5 +563,11 @@ struct JsonBlock {
     full_text: String,
     short_text: String,
     separator: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_width: Option<MinWidth>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    align: Option<Align>,
     markup: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    background: Option<String>,
 }
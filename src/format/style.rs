@@ -0,0 +1,89 @@
+use super::color::RGBA;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Where a block's text is put when the block is wider than its text, because of its minimum
+/// width.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Align {
+    /// Text is put at the left edge.
+    Left,
+
+    /// Text is centered.
+    Center,
+
+    /// Text is put at the right edge.
+    Right,
+}
+
+/// The minimum width of a block, as status bars using the JSON protocol take it.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum MinWidth {
+    /// A width in pixels.
+    Pixels(u32),
+
+    /// The width of this text, like `100%` for a block that fits any percentage.
+    Text(String),
+}
+
+/// How the outputs of a block are drawn, beyond the colors the theme gives them.
+#[derive(Clone, Debug)]
+pub struct BlockStyle {
+    /// The color of the block's icon, if it's different from the color of its text.
+    pub icon_color: Option<RGBA>,
+
+    /// Icons drawn in place of the block's own, by the icon they replace.
+    pub icons: HashMap<char, String>,
+
+    /// The minimum width of the block.
+    pub min_width: Option<MinWidth>,
+
+    /// Where the block's text is put, if the block is wider than its text.
+    pub align: Option<Align>,
+
+    /// Whether a separator is drawn after the block.
+    pub separator: bool,
+}
+
+impl Default for BlockStyle {
+    fn default() -> Self {
+        Self {
+            icon_color: None,
+            icons: HashMap::new(),
+            min_width: None,
+            align: None,
+            separator: true,
+        }
+    }
+}
+
+impl BlockStyle {
+    /// Returns the icon to draw in place of `icon`, which is `icon` itself unless it's replaced.
+    pub fn icon(&self, icon: char) -> String {
+        self.icons
+            .get(&icon)
+            .cloned()
+            .unwrap_or_else(|| icon.to_string())
+    }
+}
+
+/// Matches every icon in `tables` with the one at the same position in `icons`. If `icons` is
+/// shorter or longer than a table, it's spread over the table instead, so that three icons for an
+/// eleven-icon table are used for its lowest, middle and highest thirds.
+pub fn replace_icons(tables: &[&[char]], icons: &[String]) -> HashMap<char, String> {
+    let mut replacements = HashMap::new();
+    if icons.is_empty() {
+        return replacements;
+    }
+
+    for table in tables {
+        for (i, icon) in table.iter().enumerate() {
+            let index = i * icons.len() / table.len();
+            replacements.insert(*icon, icons[index].clone());
+        }
+    }
+
+    replacements
+}
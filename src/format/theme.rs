@@ -0,0 +1,151 @@
+use super::color::{self, RGBA};
+use crate::{config, errors::*};
+use serde::Deserialize;
+use std::{collections::HashMap, fs, io::ErrorKind, path::PathBuf, str::FromStr};
+
+/// Themes that come with muse-status, by name. `default` isn't here, because it's
+/// `Theme::default()`. Theme files with the same names take precedence over these.
+const BUILT_IN_THEMES: [(&str, &str); 3] = [
+    ("gruvbox", include_str!("themes/gruvbox.yaml")),
+    ("nord", include_str!("themes/nord.yaml")),
+    ("solarized", include_str!("themes/solarized.yaml")),
+];
+
+/// The colors and icon font that outputs are drawn with. Themes are read from YAML files, where
+/// colors are written like `#rrggbb` or `#rrggbbaa`. Anything a theme leaves out is taken from the
+/// default theme.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    /// The color of most text.
+    #[serde(deserialize_with = "color::deserialize_color")]
+    pub primary: RGBA,
+
+    /// The color of secondary and dim text.
+    #[serde(deserialize_with = "color::deserialize_color")]
+    pub secondary: RGBA,
+
+    /// The color of warnings.
+    #[serde(deserialize_with = "color::deserialize_color")]
+    pub warning: RGBA,
+
+    /// The color of alarms.
+    #[serde(deserialize_with = "color::deserialize_color")]
+    pub alarm: RGBA,
+
+    /// The background of blocks. If not set, the status bar's own background shows through.
+    #[serde(deserialize_with = "color::deserialize_optional_color")]
+    pub background: Option<RGBA>,
+
+    /// The font icons are drawn with.
+    pub icon_font: String,
+
+    /// Colors for specific blocks, by block name, which take precedence over the ones above.
+    pub blocks: HashMap<String, BlockTheme>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            primary: RGBA {
+                r: 0xff,
+                g: 0xff,
+                b: 0xff,
+                a: 0xff,
+            },
+            secondary: RGBA {
+                r: 0xc0,
+                g: 0xc0,
+                b: 0xc0,
+                a: 0xff,
+            },
+            warning: color::WARNING_COLOR,
+            alarm: color::ALARM_COLOR,
+            background: None,
+            icon_font: String::from("Material Design Icons 12"),
+            blocks: HashMap::new(),
+        }
+    }
+}
+
+/// Colors for a specific block. Colors that aren't set are taken from the rest of the theme.
+/// Warnings and alarms are always drawn in the theme's warning and alarm colors.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BlockTheme {
+    /// The color of the block's text.
+    #[serde(deserialize_with = "color::deserialize_optional_color")]
+    pub primary: Option<RGBA>,
+
+    /// The color of the block's secondary and dim text.
+    #[serde(deserialize_with = "color::deserialize_optional_color")]
+    pub secondary: Option<RGBA>,
+
+    /// The block's background.
+    #[serde(deserialize_with = "color::deserialize_optional_color")]
+    pub background: Option<RGBA>,
+}
+
+impl FromStr for Theme {
+    type Err = serde_yaml::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_yaml::from_str(s)
+    }
+}
+
+/// Returns the directory theme files are read from, `themes` next to the default config file.
+pub fn themes_dir() -> Result<PathBuf, MuseStatusError> {
+    Ok(config::default_config_path()?.with_file_name("themes"))
+}
+
+/// Returns the file a theme named `name` is read from: `<name>.yaml` in the themes directory. If
+/// `name` has a slash in it, it's the path to the file instead.
+pub fn theme_path(name: &str) -> Result<PathBuf, MuseStatusError> {
+    if name.contains('/') {
+        Ok(PathBuf::from(name))
+    } else {
+        Ok(themes_dir()?.join(format!("{}.yaml", name)))
+    }
+}
+
+/// Loads the theme named `name`, from its theme file if there is one, or from the built-in themes
+/// otherwise.
+pub fn load(name: &str) -> Result<Theme, MuseStatusError> {
+    let path = theme_path(name)?;
+
+    match fs::read_to_string(&path) {
+        Ok(s) => s.parse().map_err(|e| {
+            MuseStatusError::from(format!("couldn't read theme {}: {}", path.display(), e))
+        }),
+        Err(e) if e.kind() == ErrorKind::NotFound => built_in(name).ok_or_else(|| {
+            MuseStatusError::from(format!(
+                "there's no theme named `{}`; add {}, or use one of these: default, {}",
+                name,
+                path.display(),
+                BUILT_IN_THEMES
+                    .iter()
+                    .map(|(n, _)| *n)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        }),
+        Err(e) => Err(MuseStatusError::from(format!(
+            "couldn't read theme {}: {}",
+            path.display(),
+            e
+        ))),
+    }
+}
+
+/// Returns the built-in theme named `name`, if there is one.
+fn built_in(name: &str) -> Option<Theme> {
+    if name == "default" {
+        return Some(Theme::default());
+    }
+
+    BUILT_IN_THEMES
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, yaml)| yaml.parse().expect("built-in themes should parse"))
+}
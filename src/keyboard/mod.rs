@@ -0,0 +1,220 @@
+use crate::{
+    config::KeyboardConfig,
+    errors::*,
+    format::{
+        animation::Animation,
+        blocks::{output::*, *},
+        Attention,
+    },
+    utils,
+};
+use chrono::Duration;
+use std::path::{Path, PathBuf};
+
+pub(crate) const SYS_DIR: &str = "/sys";
+const CAPS_LOCK_ICON: char = '\u{F0632}';
+
+/// The lock keys, with the suffix of their LED names in /sys/class/leds and the name they're
+/// shown with.
+const LOCK_KEYS: [(&str, &str); 3] = [
+    ("::capslock", "Caps Lock"),
+    ("::numlock", "Num Lock"),
+    ("::scrolllock", "Scroll Lock"),
+];
+
+/// Returns the LEDs under `sys_root` (which is normally /sys) whose names end with `suffix`.
+fn find_leds(sys_root: &Path, suffix: &str) -> Vec<PathBuf> {
+    utils::sorted_dir_entries(&sys_root.join("class/leds"))
+        .into_iter()
+        .filter(|d| d.to_string_lossy().ends_with(suffix))
+        .collect()
+}
+
+/// Returns true if there's a caps lock LED under `sys_root`. Every keyboard has one, so not
+/// finding one means the block can't work.
+pub(crate) fn has_caps_lock_led(sys_root: &Path) -> bool {
+    !find_leds(sys_root, LOCK_KEYS[0].0).is_empty()
+}
+
+/// Returns which of `LOCK_KEYS` are on, in the same order. A lock is on if any keyboard has its
+/// LED lit.
+fn read_locks(sys_root: &Path) -> Result<[bool; 3], MuseStatusError> {
+    let mut locks = [false; 3];
+    for (lock, (suffix, _)) in locks.iter_mut().zip(LOCK_KEYS) {
+        for led in find_leds(sys_root, suffix) {
+            if utils::get_int_from_file(&led.join("brightness"))? > 0 {
+                *lock = true;
+                break;
+            }
+        }
+    }
+
+    Ok(locks)
+}
+
+/// A block that shows a warning while caps lock is on, along with any other lock keys that are
+/// on. It's hidden while caps lock is off.
+pub struct KeyboardBlock {
+    id: String,
+    config: KeyboardConfig,
+    locks: [bool; 3],
+}
+
+impl KeyboardBlock {
+    /// Returns a new block with the configuration provided.
+    pub fn new(config: KeyboardConfig) -> Self {
+        Self {
+            id: String::from("keyboard"),
+            config,
+            locks: [false; 3],
+        }
+    }
+
+    /// Sets the id of the block, which is used as its name. By default, the id is `keyboard`.
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = id.to_string();
+        self
+    }
+}
+
+impl Block for KeyboardBlock {
+    fn name(&self) -> &str {
+        &self.id
+    }
+
+    fn update(&mut self) -> Result<(), UpdateError> {
+        let sys_root = Path::new(&self.config.sys_root);
+        if !has_caps_lock_led(sys_root) {
+            return Err(UpdateError {
+                block_name: self.name().to_string(),
+                message: format!(
+                    "no caps lock LEDs found in {}/class/leds",
+                    sys_root.display()
+                ),
+            });
+        }
+
+        self.locks = match read_locks(sys_root) {
+            Ok(l) => l,
+            Err(e) => {
+                return Err(UpdateError {
+                    block_name: self.name().to_string(),
+                    message: format!("couldn't read lock keys: {}", e),
+                })
+            }
+        };
+
+        Ok(())
+    }
+
+    fn next_update(&self) -> Option<NextUpdate> {
+        // with an interval of 0, the block only updates with `muse-status notify`
+        match self.config.update_interval_seconds {
+            0 => None,
+            s => Some(NextUpdate::In(Duration::seconds(s.into()))),
+        }
+    }
+
+    fn output(&self) -> Option<BlockOutput> {
+        if !self.locks[0] {
+            return None;
+        }
+
+        let text = LOCK_KEYS
+            .iter()
+            .zip(self.locks)
+            .filter(|(_, on)| *on)
+            .map(|((_, name), _)| *name)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        Some(
+            BlockOutput::new(
+                self.name(),
+                Some(CAPS_LOCK_ICON),
+                BlockText::Single(text),
+                Attention::Warning,
+            )
+            .with_animation(Animation::fade_in()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{single_text, FixtureDir};
+
+    /// Returns a fixture /sys with a keyboard whose caps, num and scroll lock LEDs have the
+    /// brightnesses given.
+    fn fixture_sys(caps: u8, num: u8, scroll: u8) -> FixtureDir {
+        let sys = FixtureDir::new();
+        for (suffix, brightness) in [("capslock", caps), ("numlock", num), ("scrolllock", scroll)] {
+            sys.write(
+                &format!("class/leds/input0::{}/brightness", suffix),
+                &format!("{}\n", brightness),
+            );
+        }
+
+        sys
+    }
+
+    fn block_with(sys: &FixtureDir) -> KeyboardBlock {
+        KeyboardBlock::new(KeyboardConfig {
+            sys_root: sys.root(),
+            ..Default::default()
+        })
+    }
+
+    fn updated_block(sys: &FixtureDir) -> KeyboardBlock {
+        let mut block = block_with(sys);
+        block.update().unwrap();
+        block
+    }
+
+    #[test]
+    fn reads_lock_leds() {
+        let sys = fixture_sys(1, 0, 1);
+
+        assert!(has_caps_lock_led(sys.path()));
+        assert_eq!(read_locks(sys.path()).unwrap(), [true, false, true]);
+    }
+
+    #[test]
+    fn hides_while_caps_lock_is_off() {
+        let sys = fixture_sys(0, 1, 0);
+
+        assert_eq!(single_text(&updated_block(&sys)), None);
+    }
+
+    #[test]
+    fn shows_caps_lock() {
+        let sys = fixture_sys(1, 0, 0);
+        let block = updated_block(&sys);
+
+        assert_eq!(single_text(&block).as_deref(), Some("Caps Lock"));
+        assert_eq!(block.output().unwrap().attention(), &Attention::Warning);
+    }
+
+    #[test]
+    fn shows_other_locks_alongside_caps_lock() {
+        let sys = fixture_sys(1, 1, 0);
+
+        assert_eq!(
+            single_text(&updated_block(&sys)).as_deref(),
+            Some("Caps Lock, Num Lock")
+        );
+    }
+
+    #[test]
+    fn fails_without_a_caps_lock_led() {
+        let sys = FixtureDir::new();
+        sys.mkdir("class/leds/input0::numlock");
+
+        assert!(!has_caps_lock_led(sys.path()));
+
+        let mut block = block_with(&sys);
+        assert!(block.update().is_err());
+        assert!(block.output().is_none());
+    }
+}
@@ -13,6 +13,9 @@ pub mod brightness;
 /// The client module, used by the muse-status executable.
 pub mod client;
 
+/// The command block module.
+pub mod command;
+
 /// The config module, for user config.
 pub mod config;
 
@@ -22,23 +25,60 @@ pub mod daemon;
 /// The date block module.
 pub mod date;
 
+/// The disk space block module.
+pub mod disk;
+
 /// The errors module.
 pub mod errors;
 
+/// The feed block module, for blocks pushed by other programs.
+pub mod feed;
+
 /// The formatting module.
 pub mod format;
 
+/// The keyboard (lock keys) block module.
+pub mod keyboard;
+
 /// The network block module.
 pub mod network;
 
 /// The mpris block module.
 pub mod mpris;
 
+/// The privacy (camera and microphone) block module.
+pub mod privacy;
+
+/// The process watch block module.
+pub mod process_watch;
+
+/// The protocol module, for messages exchanged between the daemon and clients.
+pub mod protocol;
+
+/// The reminders block module.
+pub mod reminders;
+
+/// The temperature block module.
+pub mod temperature;
+
+/// The timer block module.
+pub mod timer;
+
+/// The transport module, for connections between the daemon and clients.
+pub mod transport;
+
+/// The utilization (CPU and memory) block module.
+pub mod utilization;
+
 /// The volume block module.
 pub mod volume;
 
 /// The utils module.
 pub mod utils;
 
+/// Helpers shared by tests.
+#[cfg(test)]
+mod test_utils;
+
 /// The weather block module.
 pub mod weather;
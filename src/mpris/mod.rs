@@ -1,6 +1,9 @@
 use crate::errors::*;
+use crate::format::animation::Animation;
 use crate::format::blocks::output::{BlockOutput, BlockText};
-use crate::format::blocks::{Block, BlockOutputMsg, NextUpdate};
+use crate::format::blocks::{
+    click::MouseButton, Block, BlockOutputMsg, BlockRequest, ClickEvent, NextUpdate,
+};
 use crate::format::Attention;
 use mpris as mpris_lib;
 use std::sync::mpsc::Sender;
@@ -8,25 +11,37 @@ use std::sync::{Arc, Mutex};
 use std::thread;
 use std::thread::JoinHandle;
 
+/// How many characters of a title are shown at a time. Longer titles scroll.
+const MARQUEE_WIDTH: usize = 32;
+
 /// A block that displays information about any media currently playing on the device.
 pub struct MprisBlock {
+    id: String,
+
     playing_icon: char,
     paused_icon: char,
 
     status: PlayerStatus,
     title: Option<String>,
     artist: Option<String>,
+
+    /// Set when the daemon stops the block, so that the player listener can stop too.
+    stopped: bool,
 }
 
 impl Default for MprisBlock {
     fn default() -> Self {
         MprisBlock {
+            id: String::from("mpris"),
+
             playing_icon: '\u{F0F74}',
             paused_icon: '\u{F03E4}',
 
             status: PlayerStatus::Stopped,
             title: None,
             artist: None,
+
+            stopped: false,
         }
     }
 }
@@ -37,6 +52,12 @@ impl MprisBlock {
         Default::default()
     }
 
+    /// Sets the id of the block, which is used as its name. By default, the id is `mpris`.
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = id.to_string();
+        self
+    }
+
     fn get_icon(&self) -> char {
         match self.status {
             PlayerStatus::Playing => self.playing_icon,
@@ -59,14 +80,15 @@ impl MprisBlock {
         mutex: Arc<Mutex<Box<Self>>>,
         block_sender: Sender<BlockOutputMsg>,
     ) -> Result<(), MuseStatusError> {
+        let block_name = mutex.lock().unwrap().name().to_owned();
         let mut player = mpris_lib::PlayerFinder::new()
             .map_err(|e| UpdateError {
-                block_name: "mpris".to_string(),
+                block_name: block_name.clone(),
                 message: format!("couldn't create PlayerFinder: {e}"),
             })?
             .find_active()
             .map_err(|e| UpdateError {
-                block_name: "mpris".to_string(),
+                block_name,
                 message: format!("couldn't find active player: {e}"),
             })?;
 
@@ -90,6 +112,9 @@ impl MprisBlock {
                 while let Some(Ok(e)) = events.next() {
                     // update the player data, then send the update
                     let mut block = mutex.lock().unwrap();
+                    if block.stopped {
+                        return Ok(());
+                    }
 
                     match e {
                         // update the block depending on the Event
@@ -112,6 +137,10 @@ impl MprisBlock {
 
         {
             let mut block = mutex.lock().unwrap();
+            if block.stopped {
+                return Ok(());
+            }
+
             block.status = PlayerStatus::Stopped;
             block_sender
                 .send(BlockOutputMsg::new(block.name(), block.output()))
@@ -126,12 +155,13 @@ impl Block for MprisBlock {
     fn run(
         self: Box<Self>,
         block_sender: Sender<BlockOutputMsg>,
-    ) -> (Vec<JoinHandle<()>>, Sender<()>) {
+    ) -> (Vec<JoinHandle<()>>, Sender<BlockRequest>) {
         // This might seem dumb, but MprisBlock updates are dependent on updates from the mpris
-        // client, so it will not listen to any "notify" requests
-        let (notify_tx, _) = std::sync::mpsc::channel::<()>();
+        // client, so it will not listen to any "notify" requests. Clicks are still handled, though
+        let (notify_tx, notify_rx) = std::sync::mpsc::channel::<BlockRequest>();
 
         let mutex = Arc::new(Mutex::new(self));
+        let click_mutex = mutex.clone();
         let player_listen_handle = thread::Builder::new()
             .name(String::from("mpris player listener"))
             .spawn(move || loop {
@@ -139,20 +169,69 @@ impl Block for MprisBlock {
                     eprintln!("error in main mpris block loop: {e}");
                 }
 
+                if mutex.lock().unwrap().stopped {
+                    break;
+                }
+
                 // sleep after every iteration to prevent spamming
                 thread::sleep(std::time::Duration::from_secs(5));
             })
             .unwrap();
 
-        (vec![player_listen_handle], notify_tx)
+        let request_listen_handle = thread::Builder::new()
+            .name(String::from("mpris request listener"))
+            .spawn(move || {
+                while let Ok(request) = notify_rx.recv() {
+                    if let BlockRequest::Click(e) = request {
+                        let mut block = click_mutex.lock().unwrap();
+                        if let Err(e) = block.click(&e) {
+                            eprintln!("{}", e);
+                        }
+                    }
+                }
+
+                // the daemon dropped its sender, so the block should stop. the player listener
+                // notices the next time it wakes up
+                click_mutex.lock().unwrap().stopped = true;
+            })
+            .unwrap();
+
+        (vec![player_listen_handle, request_listen_handle], notify_tx)
     }
 
     fn update(&mut self) -> Result<(), UpdateError> {
         Ok(())
     }
 
+    // left click plays or pauses, right click skips to the next track, and middle click goes back
+    // to the previous track. the output is updated through player events, like any other change
+    fn click(&mut self, event: &ClickEvent) -> Result<(), UpdateError> {
+        let to_update_error = |message: String| UpdateError {
+            block_name: self.name().to_string(),
+            message,
+        };
+
+        let player = mpris_lib::PlayerFinder::new()
+            .map_err(|e| to_update_error(format!("couldn't create PlayerFinder: {e}")))?
+            .find_active()
+            .map_err(|e| to_update_error(format!("couldn't find active player: {e}")))?;
+
+        let result = match event.button {
+            MouseButton::Left => player.play_pause(),
+            MouseButton::Right => player.next(),
+            MouseButton::Middle => player.previous(),
+            _ => return Ok(()),
+        };
+
+        result.map_err(|e| to_update_error(format!("couldn't control player: {e}")))
+    }
+
+    fn click_buttons(&self) -> &'static [MouseButton] {
+        &[MouseButton::Left, MouseButton::Middle, MouseButton::Right]
+    }
+
     fn name(&self) -> &str {
-        "mpris"
+        &self.id
     }
 
     fn next_update(&self) -> Option<NextUpdate> {
@@ -176,12 +255,16 @@ impl Block for MprisBlock {
                     // use some generic default string
                     BlockText::Single(String::from("Media is playing"))
                 };
-                Some(BlockOutput::new(
-                    self.name(),
-                    Some(self.get_icon()),
-                    text,
-                    Attention::Normal,
-                ))
+                let output =
+                    BlockOutput::new(self.name(), Some(self.get_icon()), text, Attention::Normal);
+
+                // scroll titles that are too long to show all at once
+                match &self.title {
+                    Some(t) if t.chars().count() > MARQUEE_WIDTH => {
+                        Some(output.with_animation(Animation::marquee(MARQUEE_WIDTH)))
+                    }
+                    _ => Some(output),
+                }
             }
         }
     }
@@ -36,6 +36,7 @@ pub enum NetworkType {
 
 /// A block that transmits network interface data.
 pub struct NetworkBlock {
+    id: String,
     iface_name: String,
     iface_type: NetworkType,
     sys_path: PathBuf,
@@ -47,7 +48,7 @@ impl NetworkBlock {
     /// Returns a new NetworkBlock.
     pub fn new(iface_name: &str) -> Result<Self, MuseStatusError> {
         // first, make sure the path to this interface exists
-        let sys_path = Path::new("/sys/class/net").join(iface_name);
+        let sys_path = Path::new(SYS_NET_DIR).join(iface_name);
         if !sys_path.exists() {
             return Err(MuseStatusError::Basic(BasicError {
                 message: format!("network interface `{iface_name}` doesn't exist on this system"),
@@ -56,6 +57,7 @@ impl NetworkBlock {
 
         // then we can create the block
         let block = Self {
+            id: String::from("network"),
             iface_name: String::from(iface_name),
             iface_type: get_interface_type(&sys_path),
             status: NetworkStatus::Unknown,
@@ -67,6 +69,12 @@ impl NetworkBlock {
         Ok(block)
     }
 
+    /// Sets the id of the block, which is used as its name. By default, the id is `network`.
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = id.to_string();
+        self
+    }
+
     fn packet_loss(&self) -> Result<bool, UpdateError> {
         let ping_cmd_status = Command::new("ping")
             .arg("-c")
@@ -231,9 +239,8 @@ fn get_interface_type<P: AsRef<Path>>(iface_path: P) -> NetworkType {
 }
 
 impl Block for NetworkBlock {
-    // Name returns "network"
     fn name(&self) -> &str {
-        "network"
+        &self.id
     }
 
     // Updates the network information
@@ -341,6 +348,7 @@ fn get_wireless_interface(interface_name: &str) -> Result<nl80211::Interface, Ba
     })
 }
 
+pub(crate) const SYS_NET_DIR: &str = "/sys/class/net";
 const SIGNAL_MAX_DBM: i32 = -30;
 const NOISE_FLOOR_DBM: i32 = -80;
 
@@ -0,0 +1,209 @@
+use crate::{
+    config::PrivacyConfig,
+    errors::*,
+    format::{
+        animation::Animation,
+        blocks::{output::*, *},
+        Attention,
+    },
+    utils,
+};
+use chrono::Duration;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+const CAMERA_ICON: char = '\u{F0100}';
+const MICROPHONE_ICON: char = '\u{F036C}';
+
+/// A block that shows when a camera or microphone is in use, and is hidden otherwise.
+pub struct PrivacyBlock {
+    id: String,
+    config: PrivacyConfig,
+
+    /// The names of processes that have a camera open. Empty if no camera is in use.
+    camera_users: Vec<String>,
+
+    /// Whether any watched microphone is capturing.
+    microphone_in_use: bool,
+}
+
+impl PrivacyBlock {
+    /// Returns a new block with the configuration provided.
+    pub fn new(config: PrivacyConfig) -> Self {
+        Self {
+            id: String::from("privacy"),
+            config,
+            camera_users: Vec::new(),
+            microphone_in_use: false,
+        }
+    }
+
+    /// Sets the id of the block, which is used as its name. By default, the id is `privacy`.
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = id.to_string();
+        self
+    }
+
+    /// Returns the names of processes with a watched camera open, by looking through the open
+    /// files of every process. Processes of other users can't be looked through, so they're
+    /// skipped.
+    fn find_camera_users(&self) -> Result<Vec<String>, MuseStatusError> {
+        let mut users = Vec::new();
+        if self.config.cameras.is_empty() {
+            return Ok(users);
+        }
+
+        for entry in fs::read_dir(&self.config.proc_root)? {
+            let process_dir = match entry {
+                Ok(e) => e.path(),
+                Err(_) => continue,
+            };
+            if !utils::is_pid_dir(&process_dir) {
+                continue;
+            }
+
+            let fds = match fs::read_dir(process_dir.join("fd")) {
+                Ok(f) => f,
+                Err(_) => continue,
+            };
+
+            let has_camera = fds.filter_map(|fd| fd.ok()).any(|fd| {
+                fs::read_link(fd.path())
+                    .ok()
+                    .and_then(|target| target.to_str().map(String::from))
+                    .map(|target| matches_any(&self.config.cameras, &target))
+                    .unwrap_or(false)
+            });
+
+            if has_camera {
+                let name = fs::read_to_string(process_dir.join("comm"))
+                    .map(|c| c.trim().to_string())
+                    .unwrap_or_default();
+                if !name.is_empty() && !users.contains(&name) {
+                    users.push(name);
+                }
+            }
+        }
+
+        users.sort();
+        Ok(users)
+    }
+
+    /// Returns true if a capture stream is running on any watched sound card. This works for
+    /// programs using PulseAudio or PipeWire too, since they only keep capture streams running
+    /// while something is recording.
+    fn is_microphone_in_use(&self) -> bool {
+        if self.config.microphones.is_empty() {
+            return false;
+        }
+
+        let asound_dir = PathBuf::from(&self.config.proc_root).join("asound");
+        utils::sorted_dir_entries(&asound_dir)
+            .into_iter()
+            .filter(|card_dir| {
+                let is_card = file_name_starts_with(card_dir, "card");
+                let id = fs::read_to_string(card_dir.join("id"))
+                    .map(|i| i.trim().to_string())
+                    .unwrap_or_default();
+
+                is_card && matches_any(&self.config.microphones, &id)
+            })
+            .flat_map(|card_dir| utils::sorted_dir_entries(&card_dir))
+            // capture devices are named like `pcm0c`, while playback devices end in `p`
+            .filter(|pcm_dir| {
+                file_name_starts_with(pcm_dir, "pcm")
+                    && pcm_dir
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .map(|n| n.ends_with('c'))
+                        .unwrap_or(false)
+            })
+            .flat_map(|pcm_dir| utils::sorted_dir_entries(&pcm_dir))
+            .filter(|sub_dir| file_name_starts_with(sub_dir, "sub"))
+            .any(|sub_dir| {
+                fs::read_to_string(sub_dir.join("status"))
+                    .map(|s| s.lines().any(|l| l.trim() == "state: RUNNING"))
+                    .unwrap_or(false)
+            })
+    }
+}
+
+impl Block for PrivacyBlock {
+    fn name(&self) -> &str {
+        &self.id
+    }
+
+    fn update(&mut self) -> Result<(), UpdateError> {
+        self.camera_users = match self.find_camera_users() {
+            Ok(u) => u,
+            Err(e) => {
+                return Err(UpdateError {
+                    block_name: self.name().to_string(),
+                    message: format!("couldn't look for open cameras: {}", e),
+                })
+            }
+        };
+        self.microphone_in_use = self.is_microphone_in_use();
+
+        Ok(())
+    }
+
+    fn next_update(&self) -> Option<NextUpdate> {
+        Some(NextUpdate::In(Duration::seconds(
+            self.config.update_interval_seconds.max(1).into(),
+        )))
+    }
+
+    fn output(&self) -> Option<BlockOutput> {
+        let camera_in_use = !self.camera_users.is_empty();
+
+        // one icon for each device in use, camera first
+        let mut icons = Vec::new();
+        let mut devices = Vec::new();
+        if camera_in_use {
+            icons.push(CAMERA_ICON);
+            devices.push("Camera");
+        }
+        if self.microphone_in_use {
+            icons.push(MICROPHONE_ICON);
+            devices.push("Microphone");
+        }
+        let (icon, extra_icons) = icons.split_first()?;
+        let primary_text = devices.join(", ");
+
+        let block_text = if camera_in_use {
+            BlockText::Pair(primary_text, self.camera_users.join(", "))
+        } else {
+            BlockText::Single(primary_text)
+        };
+
+        Some(
+            BlockOutput::new(
+                self.name(),
+                Some(*icon),
+                block_text,
+                Attention::WarningPulse,
+            )
+            .with_extra_icons(extra_icons.to_vec())
+            .with_animation(Animation::fade_in()),
+        )
+    }
+}
+
+/// Returns true if `s` matches any of `patterns`. A pattern ending in `*` matches anything
+/// starting with the rest of it.
+fn matches_any(patterns: &[String], s: &str) -> bool {
+    patterns.iter().any(|p| match p.strip_suffix('*') {
+        Some(prefix) => s.starts_with(prefix),
+        None => p == s,
+    })
+}
+
+fn file_name_starts_with(path: &Path, prefix: &str) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| n.starts_with(prefix))
+        .unwrap_or(false)
+}
@@ -0,0 +1,192 @@
+use crate::{
+    config::{WatchConfig, WatchRule},
+    errors::*,
+    format::{
+        animation::Animation,
+        blocks::{output::*, *},
+    },
+    utils,
+};
+use chrono::Duration;
+use regex::Regex;
+use std::{fs, path::Path};
+
+const DEFAULT_ICON: char = '\u{F0493}';
+
+/// A watch rule, with its regex compiled.
+struct CompiledRule {
+    rule: WatchRule,
+    regex: Option<Regex>,
+}
+
+impl CompiledRule {
+    /// Returns true if a process with the `comm` and `cmdline` given matches the rule. `cmdline`
+    /// is only read (by calling it) if the rule has a regex.
+    fn matches(&self, comm: &str, cmdline: &mut impl FnMut() -> String) -> bool {
+        if let Some(name) = &self.rule.name {
+            if name != comm {
+                return false;
+            }
+        }
+
+        match &self.regex {
+            Some(r) => r.is_match(&cmdline()),
+            None => true,
+        }
+    }
+}
+
+/// A block that shows an entry while processes matching any of its rules are running, and is
+/// hidden otherwise.
+pub struct ProcessWatchBlock {
+    id: String,
+    proc_root: String,
+    update_interval_seconds: u32,
+    rules: Vec<CompiledRule>,
+
+    /// The indices of the rules that matched a running process in the last update.
+    matched: Vec<usize>,
+}
+
+impl ProcessWatchBlock {
+    /// Returns a new block with the configuration provided. Returns an error if a rule has an
+    /// invalid regex, or neither a name nor a regex.
+    pub fn new(config: WatchConfig) -> Result<Self, MuseStatusError> {
+        let rules = config
+            .rules
+            .into_iter()
+            .map(|rule| {
+                if rule.name.is_none() && rule.regex.is_none() {
+                    return Err(MuseStatusError::from(BasicError {
+                        message: format!("watch rule `{}` needs a `name` or a `regex`", rule.label),
+                    }));
+                }
+
+                let regex = match &rule.regex {
+                    Some(r) => Some(Regex::new(r).map_err(|e| BasicError {
+                        message: format!("watch rule `{}` has an invalid regex: {}", rule.label, e),
+                    })?),
+                    None => None,
+                };
+
+                Ok(CompiledRule { rule, regex })
+            })
+            .collect::<Result<Vec<_>, MuseStatusError>>()?;
+
+        Ok(Self {
+            id: String::from("watch"),
+            proc_root: config.proc_root,
+            update_interval_seconds: config.update_interval_seconds,
+            rules,
+            matched: Vec::new(),
+        })
+    }
+
+    /// Sets the id of the block, which is used as its name. By default, the id is `watch`.
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = id.to_string();
+        self
+    }
+
+    fn find_matches(&self) -> Result<Vec<usize>, MuseStatusError> {
+        let mut matched = Vec::new();
+
+        for entry in fs::read_dir(&self.proc_root)? {
+            let process_dir = match entry {
+                Ok(e) => e.path(),
+                Err(_) => continue,
+            };
+            if !utils::is_pid_dir(&process_dir) {
+                continue;
+            }
+
+            // processes can exit at any time, so any that can't be read are skipped
+            let comm = match fs::read_to_string(process_dir.join("comm")) {
+                Ok(c) => c.trim().to_string(),
+                Err(_) => continue,
+            };
+
+            // reading cmdline is put off until a rule needs it, and then only done once
+            let mut cmdline_cache = None;
+            let mut cmdline = || {
+                cmdline_cache
+                    .get_or_insert_with(|| read_cmdline(&process_dir))
+                    .clone()
+            };
+
+            for (i, rule) in self.rules.iter().enumerate() {
+                if !matched.contains(&i) && rule.matches(&comm, &mut cmdline) {
+                    matched.push(i);
+                }
+            }
+
+            if matched.len() == self.rules.len() {
+                break;
+            }
+        }
+
+        matched.sort_unstable();
+        Ok(matched)
+    }
+}
+
+/// Returns the command line of a process, with its arguments separated by spaces.
+fn read_cmdline(process_dir: &Path) -> String {
+    fs::read(process_dir.join("cmdline"))
+        .map(|bytes| {
+            bytes
+                .split(|b| *b == 0)
+                .filter(|arg| !arg.is_empty())
+                .map(String::from_utf8_lossy)
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_default()
+}
+
+impl Block for ProcessWatchBlock {
+    fn name(&self) -> &str {
+        &self.id
+    }
+
+    fn update(&mut self) -> Result<(), UpdateError> {
+        self.matched = match self.find_matches() {
+            Ok(m) => m,
+            Err(e) => {
+                return Err(UpdateError {
+                    block_name: self.name().to_string(),
+                    message: format!("couldn't look through processes: {}", e),
+                })
+            }
+        };
+
+        Ok(())
+    }
+
+    fn next_update(&self) -> Option<NextUpdate> {
+        Some(NextUpdate::In(Duration::seconds(
+            self.update_interval_seconds.max(1).into(),
+        )))
+    }
+
+    fn output(&self) -> Option<BlockOutput> {
+        // the first matching rule, in config order, decides the icon and attention
+        let first = &self.rules[*self.matched.first()?].rule;
+        let labels = self
+            .matched
+            .iter()
+            .map(|i| self.rules[*i].rule.label.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        Some(
+            BlockOutput::new(
+                self.name(),
+                Some(first.icon.unwrap_or(DEFAULT_ICON)),
+                BlockText::Single(labels),
+                first.attention.clone(),
+            )
+            .with_animation(Animation::fade_in()),
+        )
+    }
+}
@@ -0,0 +1,234 @@
+use crate::errors::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    io::{ErrorKind, Read, Write},
+    time::Duration,
+};
+
+/// The version of the protocol spoken by this build of muse-status. Bump this whenever a change
+/// would break older peers; purely additive changes should add a `Capability` instead.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// The oldest protocol version this build can still talk to.
+pub const MIN_PROTOCOL_VERSION: u32 = 2;
+
+/// How long either side waits for the other during the handshake. Daemons from before the
+/// handshake existed never answer, so this is how clients tell that they're too old, and clients
+/// that connect without saying anything don't keep the daemon waiting forever.
+pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Frames larger than this are refused, so that garbage (or a client speaking the old
+/// newline-delimited protocol) can't make us allocate gigabytes.
+const MAX_FRAME_LEN: u32 = 16 * 1024 * 1024;
+
+/// An optional feature of the protocol. Each side of a connection announces which capabilities
+/// it supports, and only the capabilities both sides share are used for the rest of the session.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// Subscribing to block outputs with `ClientMsg::Subscribe`.
+    Subscribe,
+
+    /// Requesting block updates with `ClientMsg::Update`.
+    Update,
+
+    /// Sending banners with `ClientMsg::Banner` and receiving them with `DaemonMsg::Banner`.
+    Banners,
+
+    /// Forwarding clicks on blocks with `ClientMsg::Click`.
+    Clicks,
+
+    /// Being told to reload the config with `DaemonMsg::ConfigReloaded`.
+    ConfigReload,
+
+    /// Controlling timer blocks with `ClientMsg::Timer`.
+    Timers,
+
+    /// Receiving `BlockText::Progress` in block outputs. Without it, the daemon sends the
+    /// percentage as text instead.
+    Progress,
+
+    /// Receiving animations in block outputs. Without it, the daemon leaves them out.
+    Animations,
+
+    /// A capability from a newer build that this build doesn't know about. It is never shared.
+    #[serde(other)]
+    Unknown,
+}
+
+/// All capabilities supported by this build.
+pub const CAPABILITIES: &[Capability] = &[
+    Capability::Subscribe,
+    Capability::Update,
+    Capability::Banners,
+    Capability::Clicks,
+    Capability::ConfigReload,
+    Capability::Timers,
+    Capability::Progress,
+    Capability::Animations,
+];
+
+/// The first frame a client sends after connecting. Fields may be added in the future, but never
+/// removed, so that any two builds can at least tell each other that they're incompatible.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Hello {
+    /// The protocol version the sender speaks.
+    pub version: u32,
+
+    /// The oldest protocol version the sender can still talk to.
+    pub min_version: u32,
+
+    /// The capabilities the sender supports.
+    pub capabilities: Vec<Capability>,
+}
+
+impl Default for Hello {
+    fn default() -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            min_version: MIN_PROTOCOL_VERSION,
+            capabilities: CAPABILITIES.to_vec(),
+        }
+    }
+}
+
+/// The daemon's answer to a client's `Hello`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum HelloReply {
+    /// The daemon accepted the connection. The `Hello` contains the daemon's own versions and
+    /// capabilities.
+    Accepted(Hello),
+
+    /// The daemon refused the connection because the protocol versions don't overlap.
+    Rejected(Hello),
+}
+
+/// The negotiated terms of a connection, agreed upon during the handshake.
+#[derive(Clone, Debug)]
+pub struct Session {
+    /// The protocol version used for this connection.
+    pub version: u32,
+
+    /// The capabilities both sides support.
+    pub capabilities: Vec<Capability>,
+}
+
+impl Session {
+    /// Negotiates a session between our `Hello` and the peer's `Hello`. Returns an error if the
+    /// two versions ranges don't overlap.
+    pub fn negotiate(ours: &Hello, theirs: &Hello) -> Result<Self, ProtocolError> {
+        let version = ours.version.min(theirs.version);
+        if version < ours.min_version.max(theirs.min_version) {
+            return Err(ProtocolError::Incompatible {
+                ours: ours.version,
+                theirs: theirs.version,
+            });
+        }
+
+        let capabilities = ours
+            .capabilities
+            .iter()
+            .filter(|c| **c != Capability::Unknown && theirs.capabilities.contains(c))
+            .copied()
+            .collect();
+
+        Ok(Self {
+            version,
+            capabilities,
+        })
+    }
+
+    /// Returns true if both sides of the connection support the capability.
+    pub fn supports(&self, capability: Capability) -> bool {
+        self.capabilities.contains(&capability)
+    }
+}
+
+/// Performs the client side of the handshake: sends our `Hello` and waits for the daemon's reply.
+pub fn client_handshake<S: Read + Write>(stream: &mut S) -> Result<Session, MuseStatusError> {
+    let ours = Hello::default();
+    write_frame(stream, &ours)?;
+
+    match read_frame::<_, HelloReply>(stream)? {
+        Some(HelloReply::Accepted(theirs)) => Ok(Session::negotiate(&ours, &theirs)?),
+        Some(HelloReply::Rejected(theirs)) => Err(ProtocolError::Incompatible {
+            ours: ours.version,
+            theirs: theirs.version,
+        }
+        .into()),
+        None => Err(ProtocolError::NoHandshake.into()),
+    }
+}
+
+/// Performs the daemon side of the handshake: reads the client's `Hello`, then accepts or
+/// rejects it.
+pub fn daemon_handshake<S: Read + Write>(stream: &mut S) -> Result<Session, MuseStatusError> {
+    let ours = Hello::default();
+    let theirs = read_frame::<_, Hello>(stream)?.ok_or(ProtocolError::NoHandshake)?;
+
+    match Session::negotiate(&ours, &theirs) {
+        Ok(session) => {
+            write_frame(stream, &HelloReply::Accepted(ours))?;
+            Ok(session)
+        }
+        Err(e) => {
+            write_frame(stream, &HelloReply::Rejected(ours))?;
+            Err(e.into())
+        }
+    }
+}
+
+/// Writes `msg` as a single frame: a big-endian `u32` length followed by that many bytes of JSON.
+pub fn write_frame<W: Write, T: Serialize>(w: &mut W, msg: &T) -> Result<(), MuseStatusError> {
+    write_frame_bytes(w, &serde_json::to_vec(msg)?)
+}
+
+/// Writes already-serialized bytes as a single frame. Useful when the same message is sent to
+/// many peers.
+pub fn write_frame_bytes<W: Write>(w: &mut W, bytes: &[u8]) -> Result<(), MuseStatusError> {
+    let len = u32::try_from(bytes.len())
+        .ok()
+        .filter(|l| *l <= MAX_FRAME_LEN)
+        .ok_or(ProtocolError::FrameTooLarge(bytes.len()))?;
+
+    w.write_all(&len.to_be_bytes())?;
+    w.write_all(bytes)?;
+    w.flush()?;
+
+    Ok(())
+}
+
+/// Reads the raw bytes of a single frame. Returns `None` if the stream was closed cleanly before
+/// a new frame started.
+pub fn read_frame_bytes<R: Read>(r: &mut R) -> Result<Option<Vec<u8>>, MuseStatusError> {
+    let mut len_bytes = [0u8; 4];
+    match r.read_exact(&mut len_bytes) {
+        Ok(()) => (),
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+
+    // a frame that starts with a brace is almost certainly the old newline-delimited json
+    if len_bytes[0] == b'{' {
+        return Err(ProtocolError::Legacy.into());
+    }
+
+    let len = u32::from_be_bytes(len_bytes);
+    if len > MAX_FRAME_LEN {
+        return Err(ProtocolError::FrameTooLarge(len as usize).into());
+    }
+
+    let mut buf = vec![0u8; len as usize];
+    r.read_exact(&mut buf)?;
+
+    Ok(Some(buf))
+}
+
+/// Reads and deserializes a single frame. Returns `None` if the stream was closed cleanly.
+pub fn read_frame<R: Read, T: DeserializeOwned>(r: &mut R) -> Result<Option<T>, MuseStatusError> {
+    match read_frame_bytes(r)? {
+        Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+        None => Ok(None),
+    }
+}
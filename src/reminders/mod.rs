@@ -0,0 +1,254 @@
+use crate::{
+    config::{self, RemindersConfig},
+    date,
+    errors::*,
+    format::{
+        blocks::{output::*, *},
+        Attention,
+    },
+};
+use chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Weekday,
+};
+use std::{fs, path::PathBuf};
+
+const BELL_ICON: char = '\u{F009A}';
+const BELL_RINGING_ICON: char = '\u{F009E}';
+
+/// How long a reminder stays up after its time, so that it isn't missed.
+const GRACE_MINUTES: i64 = 1;
+
+/// When a reminder goes off.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Schedule {
+    /// Once, at a date and time.
+    Once(NaiveDateTime),
+
+    /// Every week on the days given, at a time. No days means every day.
+    Every {
+        /// The days of the week the reminder goes off.
+        days: Vec<Weekday>,
+
+        /// The time of day the reminder goes off.
+        time: NaiveTime,
+    },
+}
+
+impl Schedule {
+    /// Returns the first time at or after `from` that the reminder goes off, if there is one.
+    fn next_after(&self, from: DateTime<Local>) -> Option<DateTime<Local>> {
+        match self {
+            Self::Once(at) => Local
+                .from_local_datetime(at)
+                .earliest()
+                .filter(|at| *at >= from),
+            Self::Every { days, time } => (0..=7)
+                .map(|offset| from.date_naive() + Duration::days(offset))
+                .filter(|date| days.is_empty() || days.contains(&date.weekday()))
+                .filter_map(|date| Local.from_local_datetime(&date.and_time(*time)).earliest())
+                .find(|at| *at >= from),
+        }
+    }
+}
+
+/// A line of the schedule file.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Reminder {
+    pub schedule: Schedule,
+    pub text: String,
+}
+
+/// Parses a schedule file, returning the reminders in it and a message for each line that
+/// couldn't be parsed, with its line number. Lines look like these:
+///
+/// ```text
+/// # one-shot reminders have a date
+/// 2026-10-20 14:00 Dentist
+///
+/// # recurring reminders start with `every`, then the days they go off
+/// every day 12:30 Lunch
+/// every weekday 09:55 Standup
+/// every weekend 10:00 Water the plants
+/// every mon,thu 18:00 Take out the trash
+/// ```
+pub(crate) fn parse_schedule(source: &str) -> (Vec<Reminder>, Vec<(usize, String)>) {
+    let mut reminders = Vec::new();
+    let mut errors = Vec::new();
+
+    for (i, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        match parse_line(line) {
+            Ok(r) => reminders.push(r),
+            Err(e) => errors.push((i + 1, e)),
+        }
+    }
+
+    (reminders, errors)
+}
+
+fn parse_line(line: &str) -> Result<Reminder, String> {
+    let mut words = line.split_whitespace();
+    let first = words.next().unwrap_or_default();
+
+    let schedule = if first == "every" {
+        let days = parse_days(words.next().ok_or("`every` needs some days")?)?;
+        let time = parse_time(words.next().ok_or("a time is missing")?)?;
+        Schedule::Every { days, time }
+    } else {
+        let date = NaiveDate::parse_from_str(first, "%Y-%m-%d").map_err(|_| {
+            format!(
+                "`{}` isn't `every` or a date like 2026-10-20, so the line can't be read",
+                first
+            )
+        })?;
+        let time = parse_time(words.next().ok_or("a time is missing")?)?;
+        Schedule::Once(date.and_time(time))
+    };
+
+    let text = words.collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        return Err(String::from("the reminder has no text"));
+    }
+
+    Ok(Reminder { schedule, text })
+}
+
+/// Parses `day`, `weekday`, `weekend`, or a list of days like `mon,wed,fri`.
+fn parse_days(s: &str) -> Result<Vec<Weekday>, String> {
+    use Weekday::*;
+
+    match s {
+        "day" => Ok(Vec::new()),
+        "weekday" => Ok(vec![Mon, Tue, Wed, Thu, Fri]),
+        "weekend" => Ok(vec![Sat, Sun]),
+        _ => s
+            .split(',')
+            .map(|d| {
+                d.parse::<Weekday>()
+                    .map_err(|_| format!("`{}` isn't a day of the week", d))
+            })
+            .collect(),
+    }
+}
+
+fn parse_time(s: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(s, "%H:%M").map_err(|_| format!("`{}` isn't a time like 09:55", s))
+}
+
+/// Returns the schedule file for a block: `file` if it's set, or `reminders` next to the default
+/// config file.
+pub(crate) fn schedule_path(config: &RemindersConfig) -> Result<PathBuf, MuseStatusError> {
+    match &config.file {
+        Some(f) => Ok(PathBuf::from(f)),
+        None => Ok(config::default_config_path()?.with_file_name("reminders")),
+    }
+}
+
+/// A block that shows the next reminder from a schedule file, once it's close enough.
+pub struct RemindersBlock {
+    id: String,
+    config: RemindersConfig,
+    now: DateTime<Local>,
+
+    /// The next reminder in the window, with the time it goes off.
+    upcoming: Option<(DateTime<Local>, String)>,
+}
+
+impl RemindersBlock {
+    /// Returns a new block with the configuration provided.
+    pub fn new(config: RemindersConfig) -> Self {
+        Self {
+            id: String::from("reminders"),
+            config,
+            now: Local::now(),
+            upcoming: None,
+        }
+    }
+
+    /// Sets the id of the block, which is used as its name. By default, the id is `reminders`.
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = id.to_string();
+        self
+    }
+
+    /// Returns the number of whole minutes until the upcoming reminder, rounded up.
+    fn minutes_left(&self) -> Option<i64> {
+        let (at, _) = self.upcoming.as_ref()?;
+        let seconds = (*at - self.now).num_seconds();
+
+        Some((seconds + 59).div_euclid(60))
+    }
+}
+
+impl Block for RemindersBlock {
+    fn name(&self) -> &str {
+        &self.id
+    }
+
+    fn update(&mut self) -> Result<(), UpdateError> {
+        self.now = Local::now();
+        self.upcoming = None;
+
+        // the file is read on every update, so that changes show up within a minute
+        let source = schedule_path(&self.config)
+            .and_then(|p| Ok(fs::read_to_string(p)?))
+            .map_err(|e| UpdateError {
+                block_name: self.name().to_string(),
+                message: format!("couldn't read the schedule file: {}", e),
+            })?;
+        let (reminders, errors) = parse_schedule(&source);
+
+        let from = self.now - Duration::minutes(GRACE_MINUTES);
+        let until = self.now + Duration::minutes(self.config.window_minutes.into());
+        self.upcoming = reminders
+            .into_iter()
+            .filter_map(|r| Some((r.schedule.next_after(from)?, r.text)))
+            .filter(|(at, _)| *at <= until)
+            .min_by_key(|(at, _)| *at);
+
+        match errors.first() {
+            Some((line, message)) => Err(UpdateError {
+                block_name: self.name().to_string(),
+                message: format!("line {} of the schedule file: {}", line, message),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn next_update(&self) -> Option<NextUpdate> {
+        // reminders are set to the minute, so waking up at every minute is exactly on time
+        Some(NextUpdate::At(date::get_next_minute()))
+    }
+
+    fn output(&self) -> Option<BlockOutput> {
+        let (at, text) = self.upcoming.as_ref()?;
+        let minutes_left = self.minutes_left()?;
+
+        let when = if minutes_left <= 0 {
+            String::from("now")
+        } else if minutes_left <= 60 {
+            format!("in {} min", minutes_left)
+        } else {
+            format!("at {}", at.format(date::TIME_FORMAT))
+        };
+
+        let (icon, attention) = if minutes_left <= self.config.alarm_minutes.into() {
+            (BELL_RINGING_ICON, Attention::Alarm)
+        } else if minutes_left <= self.config.warning_minutes.into() {
+            (BELL_ICON, Attention::Warning)
+        } else {
+            (BELL_ICON, Attention::Normal)
+        };
+
+        Some(BlockOutput::new(
+            self.name(),
+            Some(icon),
+            BlockText::Pair(text.clone(), when),
+            attention,
+        ))
+    }
+}
@@ -0,0 +1,339 @@
+use crate::{
+    config::TemperatureConfig,
+    errors::*,
+    format::{
+        blocks::{output::*, *},
+        Attention,
+    },
+    utils,
+};
+use chrono::Duration;
+use std::{fs, path::Path};
+
+pub(crate) const SYS_DIR: &str = "/sys";
+const THERMOMETER_ICON: char = '\u{F050F}';
+
+/// A temperature sensor, with a reading in degrees Celsius.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Sensor {
+    /// The label of the sensor (like `Package id 0` or `Tctl`), or the name of its device if it
+    /// doesn't have one.
+    pub label: String,
+
+    /// The name of the device the sensor belongs to (like `coretemp`, or `x86_pkg_temp` for
+    /// thermal zones).
+    pub device: String,
+
+    /// The temperature in degrees Celsius.
+    pub celsius: f32,
+}
+
+impl Sensor {
+    /// Returns true if `name` is the label of the sensor or the name of its device.
+    pub(crate) fn is_called(&self, name: &str) -> bool {
+        self.label == name || self.device == name
+    }
+}
+
+/// Returns every temperature sensor found in hwmon and thermal zones under `sys_root` (which is
+/// normally /sys). Sensors that can't be read are skipped.
+pub(crate) fn read_sensors(sys_root: &Path) -> Vec<Sensor> {
+    let mut sensors = Vec::new();
+
+    // hwmon devices have a `name` and any number of `temp<n>_input` files, each with an optional
+    // `temp<n>_label`
+    for device_dir in utils::sorted_dir_entries(&sys_root.join("class/hwmon")) {
+        let device = read_trimmed(&device_dir.join("name")).unwrap_or_default();
+
+        for input in utils::sorted_dir_entries(&device_dir) {
+            let file_name = match input.file_name().and_then(|n| n.to_str()) {
+                Some(n) => n.to_string(),
+                None => continue,
+            };
+            let prefix = match file_name.strip_suffix("_input") {
+                Some(p) if p.starts_with("temp") => p,
+                _ => continue,
+            };
+
+            if let Some(celsius) = read_millidegrees(&input) {
+                let label = read_trimmed(&device_dir.join(format!("{}_label", prefix)))
+                    .unwrap_or_else(|| format!("{} {}", device, prefix));
+                sensors.push(Sensor {
+                    label,
+                    device: device.clone(),
+                    celsius,
+                });
+            }
+        }
+    }
+
+    // thermal zones have one `temp` each, and a `type` that works as both label and device name
+    for zone_dir in utils::sorted_dir_entries(&sys_root.join("class/thermal")) {
+        let is_zone = zone_dir
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map(|n| n.starts_with("thermal_zone"))
+            .unwrap_or(false);
+        if !is_zone {
+            continue;
+        }
+
+        if let Some(celsius) = read_millidegrees(&zone_dir.join("temp")) {
+            let zone_type = read_trimmed(&zone_dir.join("type")).unwrap_or_default();
+            sensors.push(Sensor {
+                label: zone_type.clone(),
+                device: zone_type,
+                celsius,
+            });
+        }
+    }
+
+    sensors
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+/// Reads a temperature in millidegrees Celsius, like sysfs reports them.
+fn read_millidegrees(path: &Path) -> Option<f32> {
+    read_trimmed(path)?
+        .parse::<i64>()
+        .ok()
+        .map(|m| m as f32 / 1000.0)
+}
+
+/// A block that shows the temperature of the hottest sensor, or of a chosen one.
+pub struct TemperatureBlock {
+    id: String,
+    config: TemperatureConfig,
+    current: Option<Sensor>,
+}
+
+impl TemperatureBlock {
+    /// Returns a new block with the configuration provided.
+    pub fn new(config: TemperatureConfig) -> Self {
+        Self {
+            id: String::from("temperature"),
+            config,
+            current: None,
+        }
+    }
+
+    /// Sets the id of the block, which is used as its name. By default, the id is `temperature`.
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = id.to_string();
+        self
+    }
+
+    /// Returns true if the temperature is at or above the warning level. If no sensor has been
+    /// read, the method returns false.
+    fn is_warning(&self) -> bool {
+        match &self.current {
+            Some(s) => s.celsius >= self.config.warning_level,
+            None => false,
+        }
+    }
+
+    /// Returns true if the temperature is at or above the alarm level. If no sensor has been
+    /// read, the method returns false.
+    fn is_alarm(&self) -> bool {
+        match &self.current {
+            Some(s) => s.celsius >= self.config.alarm_level,
+            None => false,
+        }
+    }
+}
+
+impl Block for TemperatureBlock {
+    fn name(&self) -> &str {
+        &self.id
+    }
+
+    fn update(&mut self) -> Result<(), UpdateError> {
+        let sensors = read_sensors(Path::new(&self.config.sys_root));
+
+        self.current = match &self.config.sensor {
+            Some(name) => sensors.into_iter().find(|s| s.is_called(name)),
+            None => sensors
+                .into_iter()
+                .max_by(|a, b| a.celsius.total_cmp(&b.celsius)),
+        };
+
+        if self.current.is_none() {
+            return Err(UpdateError {
+                block_name: self.name().to_string(),
+                message: match &self.config.sensor {
+                    Some(name) => format!("couldn't find a temperature sensor called `{}`", name),
+                    None => String::from("couldn't find any temperature sensors"),
+                },
+            });
+        }
+
+        Ok(())
+    }
+
+    fn next_update(&self) -> Option<NextUpdate> {
+        Some(NextUpdate::In(Duration::seconds(
+            self.config.update_interval_seconds.max(1).into(),
+        )))
+    }
+
+    fn output(&self) -> Option<BlockOutput> {
+        let sensor = self.current.as_ref()?;
+        let temperature = format!("{:.0}°C", sensor.celsius);
+
+        // the hottest sensor can change between updates, so say which one it is
+        let block_text = match &self.config.sensor {
+            Some(_) => BlockText::Single(temperature),
+            None => BlockText::Pair(temperature, sensor.label.clone()),
+        };
+
+        let attention = if self.is_alarm() {
+            Attention::AlarmPulse
+        } else if self.is_warning() {
+            Attention::Warning
+        } else {
+            Attention::Normal
+        };
+
+        Some(BlockOutput::new(
+            self.name(),
+            Some(THERMOMETER_ICON),
+            block_text,
+            attention,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{pair_text, single_text, FixtureDir};
+
+    /// Returns a fixture /sys with two hwmon devices and a thermal zone.
+    fn fixture_sys() -> FixtureDir {
+        let sys = FixtureDir::new();
+        sys.write("class/hwmon/hwmon0/name", "coretemp\n")
+            .write("class/hwmon/hwmon0/temp1_input", "45000\n")
+            .write("class/hwmon/hwmon0/temp1_label", "Package id 0\n")
+            .write("class/hwmon/hwmon0/temp1_max", "100000\n")
+            .write("class/hwmon/hwmon0/temp2_input", "52000\n")
+            .write("class/hwmon/hwmon0/temp2_label", "Core 0\n")
+            .write("class/hwmon/hwmon1/name", "nvme\n")
+            .write("class/hwmon/hwmon1/temp1_input", "38000\n")
+            .write("class/hwmon/hwmon1/temp2_input", "not a number\n")
+            .write("class/thermal/thermal_zone0/type", "x86_pkg_temp\n")
+            .write("class/thermal/thermal_zone0/temp", "61000\n")
+            .write("class/thermal/cooling_device0/temp", "99000\n");
+
+        sys
+    }
+
+    fn block_with(sys: &FixtureDir, config: TemperatureConfig) -> TemperatureBlock {
+        TemperatureBlock::new(TemperatureConfig {
+            sys_root: sys.root(),
+            ..config
+        })
+    }
+
+    fn sensor(label: &str, device: &str, celsius: f32) -> Sensor {
+        Sensor {
+            label: label.to_string(),
+            device: device.to_string(),
+            celsius,
+        }
+    }
+
+    #[test]
+    fn reads_hwmon_and_thermal_zones() {
+        let sys = fixture_sys();
+
+        assert_eq!(
+            read_sensors(sys.path()),
+            vec![
+                sensor("Package id 0", "coretemp", 45.0),
+                sensor("Core 0", "coretemp", 52.0),
+                sensor("nvme temp1", "nvme", 38.0),
+                sensor("x86_pkg_temp", "x86_pkg_temp", 61.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn shows_the_hottest_sensor() {
+        let sys = fixture_sys();
+        let mut block = block_with(&sys, Default::default());
+        block.update().unwrap();
+
+        assert_eq!(
+            pair_text(&block),
+            Some((String::from("61°C"), String::from("x86_pkg_temp")))
+        );
+        assert_eq!(block.output().unwrap().attention(), &Attention::Normal);
+    }
+
+    #[test]
+    fn shows_a_sensor_by_label_or_device() {
+        let sys = fixture_sys();
+
+        for (name, expected) in [("Core 0", "52°C"), ("nvme", "38°C")] {
+            let mut block = block_with(
+                &sys,
+                TemperatureConfig {
+                    sensor: Some(name.to_string()),
+                    ..Default::default()
+                },
+            );
+            block.update().unwrap();
+
+            assert_eq!(
+                single_text(&block).as_deref(),
+                Some(expected),
+                "sensor `{}`",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn fails_without_the_named_sensor() {
+        let sys = fixture_sys();
+        let mut block = block_with(
+            &sys,
+            TemperatureConfig {
+                sensor: Some(String::from("Tctl")),
+                ..Default::default()
+            },
+        );
+
+        assert!(block.update().is_err());
+        assert!(block.output().is_none());
+    }
+
+    #[test]
+    fn maps_thresholds_to_attention() {
+        let cases = [
+            ("79999", Attention::Normal),
+            ("80000", Attention::Warning),
+            ("94500", Attention::Warning),
+            ("95000", Attention::AlarmPulse),
+        ];
+
+        for (millidegrees, expected) in cases {
+            let sys = FixtureDir::new();
+            sys.write("class/thermal/thermal_zone0/type", "acpitz")
+                .write("class/thermal/thermal_zone0/temp", millidegrees);
+
+            let mut block = block_with(&sys, Default::default());
+            block.update().unwrap();
+
+            assert_eq!(
+                block.output().unwrap().attention(),
+                &expected,
+                "{} millidegrees",
+                millidegrees
+            );
+        }
+    }
+}
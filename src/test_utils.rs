@@ -0,0 +1,61 @@
+use crate::format::blocks::{output::BlockText, Block};
+use std::{fs, path::Path};
+use tempfile::TempDir;
+
+/// A temporary directory standing in for /sys or /proc, so that blocks can be tested against
+/// files written by the test.
+pub(crate) struct FixtureDir(TempDir);
+
+impl FixtureDir {
+    /// Returns a new, empty fixture directory.
+    pub(crate) fn new() -> Self {
+        Self(TempDir::new().unwrap())
+    }
+
+    /// Writes `content` to `path` under the directory, making its parent directories first.
+    pub(crate) fn write(&self, path: &str, content: &str) -> &Self {
+        let path = self.0.path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+        self
+    }
+
+    /// Makes the directory `path` under the directory, along with its parents.
+    pub(crate) fn mkdir(&self, path: &str) -> &Self {
+        fs::create_dir_all(self.0.path().join(path)).unwrap();
+        self
+    }
+
+    /// Removes the file at `path` under the directory.
+    pub(crate) fn remove(&self, path: &str) {
+        fs::remove_file(self.0.path().join(path)).unwrap();
+    }
+
+    /// Returns the path of the directory.
+    pub(crate) fn path(&self) -> &Path {
+        self.0.path()
+    }
+
+    /// Returns the path of the directory as a string, like blocks take it in their configs.
+    pub(crate) fn root(&self) -> String {
+        self.0.path().to_string_lossy().into_owned()
+    }
+}
+
+/// Returns the text of the block's output, or `None` if the block is hidden. Panics if the
+/// output's text isn't `BlockText::Single`.
+pub(crate) fn single_text(block: &impl Block) -> Option<String> {
+    block.output().map(|o| match o.text() {
+        BlockText::Single(t) => t.clone(),
+        t => panic!("expected a single text, but got {:?}", t),
+    })
+}
+
+/// Returns the primary and secondary text of the block's output, or `None` if the block is
+/// hidden. Panics if the output's text isn't `BlockText::Pair`.
+pub(crate) fn pair_text(block: &impl Block) -> Option<(String, String)> {
+    block.output().map(|o| match o.text() {
+        BlockText::Pair(p, s) => (p.clone(), s.clone()),
+        t => panic!("expected a pair of texts, but got {:?}", t),
+    })
+}
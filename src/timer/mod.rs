@@ -0,0 +1,365 @@
+use crate::{
+    config::TimerConfig,
+    errors::*,
+    format::{
+        blocks::{output::*, *},
+        Attention, Banner,
+    },
+};
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf, sync::mpsc::Sender};
+
+const TIMER_ICON: char = '\u{F051B}';
+const FINISHED_ICON: char = '\u{F009E}';
+
+/// A command for timer blocks, sent with `muse-status timer`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum TimerCommand {
+    /// Starts a timer. If `seconds` is set, the timer counts down from it; otherwise, it's a
+    /// stopwatch. A timer with the same name is replaced.
+    Start {
+        /// The name of the timer.
+        name: String,
+
+        /// How long the countdown lasts, if it's a countdown.
+        seconds: Option<f64>,
+    },
+
+    /// Pauses a running timer, by name.
+    Pause(String),
+
+    /// Resumes a paused timer, by name.
+    Resume(String),
+
+    /// Stops and removes a timer, by name.
+    Cancel(String),
+}
+
+/// A countdown or stopwatch. Times are kept as wall-clock times so that timers keep running
+/// while the daemon isn't.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Timer {
+    name: String,
+
+    /// How long the countdown lasts, in seconds. `None` for stopwatches.
+    countdown_seconds: Option<f64>,
+
+    /// Time counted before the timer was last resumed (or started), in seconds.
+    counted_seconds: f64,
+
+    /// When the timer was last resumed (or started). `None` while paused.
+    running_since: Option<DateTime<Local>>,
+
+    /// Whether the banner for this countdown finishing has been sent.
+    #[serde(default)]
+    finish_announced: bool,
+}
+
+impl Timer {
+    fn new(name: &str, countdown_seconds: Option<f64>) -> Self {
+        Self {
+            name: name.to_string(),
+            countdown_seconds,
+            counted_seconds: 0.0,
+            running_since: Some(Local::now()),
+            finish_announced: false,
+        }
+    }
+
+    /// Returns the time counted so far, in seconds.
+    fn elapsed_seconds(&self, now: DateTime<Local>) -> f64 {
+        let running = self
+            .running_since
+            .map(|since| (now - since).num_milliseconds() as f64 / 1000.0)
+            .unwrap_or(0.0);
+
+        self.counted_seconds + running.max(0.0)
+    }
+
+    /// Returns true if the timer is a countdown that has run out.
+    fn is_finished(&self, now: DateTime<Local>) -> bool {
+        match self.countdown_seconds {
+            Some(s) => self.elapsed_seconds(now) >= s,
+            None => false,
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        self.running_since.is_some()
+    }
+
+    fn pause(&mut self, now: DateTime<Local>) {
+        self.counted_seconds = self.elapsed_seconds(now);
+        self.running_since = None;
+    }
+
+    fn resume(&mut self, now: DateTime<Local>) {
+        if self.running_since.is_none() {
+            self.running_since = Some(now);
+        }
+    }
+
+    /// Returns the time shown for the timer: the time left for countdowns, and the time counted
+    /// for stopwatches.
+    fn display(&self, now: DateTime<Local>) -> String {
+        if self.is_finished(now) {
+            return format!("{} done", self.name);
+        }
+
+        let elapsed = self.elapsed_seconds(now);
+        let shown = match self.countdown_seconds {
+            // round up, so that a countdown shows 0:00 only when it's done
+            Some(s) => (s - elapsed).ceil(),
+            None => elapsed.floor(),
+        };
+
+        let time = format_seconds(shown.max(0.0) as u64);
+        if self.is_running() {
+            format!("{} {}", self.name, time)
+        } else {
+            format!("{} {} (paused)", self.name, time)
+        }
+    }
+}
+
+/// Formats seconds like `4:05` or `1:02:03`.
+fn format_seconds(seconds: u64) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+/// A block that shows named countdowns and stopwatches, controlled with `muse-status timer`.
+/// Timers are saved to disk, so they survive the daemon restarting.
+pub struct TimerBlock {
+    id: String,
+    config: TimerConfig,
+    timers: Vec<Timer>,
+
+    /// Whether timers have been loaded from disk yet. They're loaded on first use, so that the
+    /// block's id (which names the file) is known.
+    loaded: bool,
+
+    banner_sender: Option<Sender<Banner>>,
+}
+
+impl TimerBlock {
+    /// Returns a new block with the configuration provided.
+    pub fn new(config: TimerConfig) -> Self {
+        Self {
+            id: String::from("timer"),
+            config,
+            timers: Vec::new(),
+            loaded: false,
+            banner_sender: None,
+        }
+    }
+
+    /// Sets the id of the block, which is used as its name. By default, the id is `timer`.
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = id.to_string();
+        self
+    }
+
+    /// Returns the file timers are saved to: `state_file` if it's set, or a file named after the
+    /// block in the user's data directory.
+    fn state_path(&self) -> Option<PathBuf> {
+        match &self.config.state_file {
+            Some(f) => Some(PathBuf::from(f)),
+            None => dirs::data_dir().map(|d| {
+                d.join("muse-status")
+                    .join("timers")
+                    .join(format!("{}.json", self.id))
+            }),
+        }
+    }
+
+    fn load(&mut self) -> Result<(), MuseStatusError> {
+        if self.loaded {
+            return Ok(());
+        }
+        self.loaded = true;
+
+        let path = match self.state_path() {
+            Some(p) if p.exists() => p,
+            _ => return Ok(()),
+        };
+        self.timers = serde_json::from_slice(&fs::read(path)?)?;
+
+        Ok(())
+    }
+
+    fn save(&self) -> Result<(), MuseStatusError> {
+        let path = self.state_path().ok_or_else(|| BasicError {
+            message: String::from("couldn't figure out where to save timers"),
+        })?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        Ok(fs::write(path, serde_json::to_vec(&self.timers)?)?)
+    }
+
+    fn find(&mut self, name: &str) -> Result<&mut Timer, MuseStatusError> {
+        self.timers
+            .iter_mut()
+            .find(|t| t.name == name)
+            .ok_or_else(|| MuseStatusError::from(format!("there's no timer called `{}`", name)))
+    }
+
+    fn apply(&mut self, command: &TimerCommand) -> Result<(), MuseStatusError> {
+        self.load()?;
+
+        let now = Local::now();
+        match command {
+            TimerCommand::Start { name, seconds } => {
+                self.timers.retain(|t| &t.name != name);
+                self.timers.push(Timer::new(name, *seconds));
+            }
+            TimerCommand::Pause(name) => self.find(name)?.pause(now),
+            TimerCommand::Resume(name) => self.find(name)?.resume(now),
+            TimerCommand::Cancel(name) => {
+                self.find(name)?;
+                self.timers.retain(|t| &t.name != name);
+            }
+        }
+
+        self.save()
+    }
+
+    /// Sends a banner for each countdown that finished since the last update. Returns true if any
+    /// did.
+    fn announce_finished(&mut self) -> bool {
+        let now = Local::now();
+        let mut any_finished = false;
+
+        for timer in &mut self.timers {
+            if timer.finish_announced || !timer.is_finished(now) {
+                continue;
+            }
+
+            timer.finish_announced = true;
+            any_finished = true;
+
+            if let Some(sender) = &self.banner_sender {
+                let _ = sender.send(
+                    Banner::new(
+                        &format!("{}:{}", self.id, timer.name),
+                        &format!("Timer `{}` is done!", timer.name),
+                        10.0,
+                    )
+                    .with_attention(Attention::AlarmPulse),
+                );
+            }
+        }
+
+        any_finished
+    }
+}
+
+impl Block for TimerBlock {
+    fn name(&self) -> &str {
+        &self.id
+    }
+
+    fn update(&mut self) -> Result<(), UpdateError> {
+        let result = self.load().and_then(|_| {
+            if self.announce_finished() {
+                self.save()?;
+            }
+
+            Ok(())
+        });
+
+        result.map_err(|e| UpdateError {
+            block_name: self.name().to_string(),
+            message: format!("couldn't load or save timers: {}", e),
+        })
+    }
+
+    fn set_banner_sender(&mut self, banner_sender: Sender<Banner>) {
+        self.banner_sender = Some(banner_sender);
+    }
+
+    fn click(&mut self, event: &ClickEvent) -> Result<(), UpdateError> {
+        // left clicking dismisses finished countdowns
+        if event.button != click::MouseButton::Left {
+            return Ok(());
+        }
+
+        let result = self.load().and_then(|_| {
+            let now = Local::now();
+            self.timers.retain(|t| !t.is_finished(now));
+            self.save()
+        });
+
+        result.map_err(|e| UpdateError {
+            block_name: self.name().to_string(),
+            message: format!("couldn't save timers: {}", e),
+        })
+    }
+
+    fn click_buttons(&self) -> &'static [click::MouseButton] {
+        &[click::MouseButton::Left]
+    }
+
+    fn command(&mut self, command: &str) -> Result<(), UpdateError> {
+        serde_json::from_str(command)
+            .map_err(MuseStatusError::from)
+            .and_then(|c| self.apply(&c))
+            .map_err(|e| UpdateError {
+                block_name: self.name().to_string(),
+                message: e.to_string(),
+            })
+    }
+
+    fn next_update(&self) -> Option<NextUpdate> {
+        // running timers are redrawn every second. otherwise, only requests change anything
+        let now = Local::now();
+        if self
+            .timers
+            .iter()
+            .any(|t| t.is_running() && !t.is_finished(now))
+        {
+            Some(NextUpdate::In(Duration::seconds(1)))
+        } else {
+            None
+        }
+    }
+
+    fn output(&self) -> Option<BlockOutput> {
+        if self.timers.is_empty() {
+            return None;
+        }
+
+        let now = Local::now();
+        let any_finished = self.timers.iter().any(|t| t.is_finished(now));
+        let all_paused = self.timers.iter().all(|t| !t.is_running());
+
+        let (icon, attention) = if any_finished {
+            (FINISHED_ICON, Attention::AlarmPulse)
+        } else if all_paused {
+            (TIMER_ICON, Attention::Dim)
+        } else {
+            (TIMER_ICON, Attention::Normal)
+        };
+
+        let text = self
+            .timers
+            .iter()
+            .map(|t| t.display(now))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        Some(BlockOutput::new(
+            self.name(),
+            Some(icon),
+            BlockText::Single(text),
+            attention,
+        ))
+    }
+}
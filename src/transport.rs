@@ -0,0 +1,262 @@
+use crate::errors::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display},
+    fs::{self, DirBuilder},
+    io::{self, Read, Write},
+    net::{TcpListener, TcpStream},
+    os::unix::{
+        fs::{DirBuilderExt, FileTypeExt, MetadataExt, PermissionsExt},
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// The name of the socket file created by the daemon.
+const SOCKET_FILE_NAME: &str = "daemon.sock";
+
+/// The address the daemon listens on and clients connect to. Parsed from the `daemon_addr` config
+/// value: `tcp:<host>:<port>` (or a bare `<host>:<port>`) uses TCP, while `unix:<path>` (or
+/// anything that looks like a path) uses a Unix domain socket.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(from = "String", into = "String")]
+pub enum DaemonAddr {
+    /// A Unix domain socket at the given path.
+    Unix(PathBuf),
+
+    /// A TCP address, like `localhost:2899`.
+    Tcp(String),
+}
+
+impl Default for DaemonAddr {
+    fn default() -> Self {
+        Self::Unix(default_socket_path())
+    }
+}
+
+impl From<String> for DaemonAddr {
+    fn from(s: String) -> Self {
+        if let Some(addr) = s.strip_prefix("tcp:") {
+            Self::Tcp(addr.to_string())
+        } else if let Some(path) = s.strip_prefix("unix:") {
+            Self::Unix(PathBuf::from(path))
+        } else if s.contains('/') {
+            Self::Unix(PathBuf::from(s))
+        } else {
+            // older configs only had `host:port` addresses, so treat anything else as TCP
+            Self::Tcp(s)
+        }
+    }
+}
+
+impl From<DaemonAddr> for String {
+    fn from(addr: DaemonAddr) -> Self {
+        addr.to_string()
+    }
+}
+
+impl Display for DaemonAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unix(path) => write!(f, "unix:{}", path.display()),
+            Self::Tcp(addr) => write!(f, "tcp:{}", addr),
+        }
+    }
+}
+
+/// Returns `$XDG_RUNTIME_DIR/muse-status/daemon.sock`. If there is no runtime directory, the
+/// system's temporary directory is used instead, with the user's id in the directory name so that
+/// users don't share it.
+pub fn default_socket_path() -> PathBuf {
+    default_socket_dir().join(SOCKET_FILE_NAME)
+}
+
+/// Returns the directory of the default socket path.
+fn default_socket_dir() -> PathBuf {
+    match dirs::runtime_dir() {
+        Some(d) => d.join("muse-status"),
+        // SAFETY: getuid can't fail
+        None => std::env::temp_dir().join(format!("muse-status-{}", unsafe { libc::getuid() })),
+    }
+}
+
+/// A connection between the daemon and a client, over either transport.
+#[derive(Debug)]
+pub enum Stream {
+    /// A Unix domain socket connection.
+    Unix(UnixStream),
+
+    /// A TCP connection.
+    Tcp(TcpStream),
+}
+
+impl Stream {
+    /// Connects to the daemon at `addr`.
+    pub fn connect(addr: &DaemonAddr) -> io::Result<Self> {
+        match addr {
+            DaemonAddr::Unix(path) => UnixStream::connect(path).map(Self::Unix),
+            DaemonAddr::Tcp(a) => TcpStream::connect(a).map(Self::Tcp),
+        }
+    }
+
+    /// Sets the read timeout of the connection. `None` blocks indefinitely.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        match self {
+            Self::Unix(s) => s.set_read_timeout(timeout),
+            Self::Tcp(s) => s.set_read_timeout(timeout),
+        }
+    }
+
+    /// Creates a new independently owned handle to the same connection.
+    pub fn try_clone(&self) -> io::Result<Self> {
+        match self {
+            Self::Unix(s) => s.try_clone().map(Self::Unix),
+            Self::Tcp(s) => s.try_clone().map(Self::Tcp),
+        }
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Unix(s) => s.read(buf),
+            Self::Tcp(s) => s.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Unix(s) => s.write(buf),
+            Self::Tcp(s) => s.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Unix(s) => s.flush(),
+            Self::Tcp(s) => s.flush(),
+        }
+    }
+}
+
+/// Listens for client connections on either transport.
+pub enum Listener {
+    /// Listens on a Unix domain socket. The socket file is removed when the listener is dropped.
+    Unix(UnixListener, PathBuf),
+
+    /// Listens on a TCP address.
+    Tcp(TcpListener),
+}
+
+impl Listener {
+    /// Starts listening on `addr`. For Unix sockets, two things keep other users away from the
+    /// socket: the socket itself is given mode 0600, and its directory is created with mode 0700
+    /// if it's missing. The default directory (which may be in the shared temporary directory)
+    /// also has to belong to the current user and be closed to everyone else, in case another
+    /// user made it first. A stale socket left behind by a crashed daemon is removed, but if
+    /// another daemon is still listening on it, or if the path isn't a socket, an error is
+    /// returned instead.
+    pub fn bind(addr: &DaemonAddr) -> Result<Self, MuseStatusError> {
+        match addr {
+            DaemonAddr::Unix(path) => {
+                if let Some(parent) = path.parent() {
+                    DirBuilder::new()
+                        .recursive(true)
+                        .mode(0o700)
+                        .create(parent)?;
+                    if parent == default_socket_dir() {
+                        check_socket_dir(parent)?;
+                    }
+                }
+
+                remove_stale_socket(path)?;
+
+                let listener = UnixListener::bind(path)?;
+                fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+
+                Ok(Self::Unix(listener, path.clone()))
+            }
+            DaemonAddr::Tcp(a) => Ok(Self::Tcp(TcpListener::bind(a)?)),
+        }
+    }
+
+    /// Waits for and returns the next client connection.
+    pub fn accept(&self) -> io::Result<Stream> {
+        match self {
+            Self::Unix(l, _) => l.accept().map(|(s, _)| Stream::Unix(s)),
+            Self::Tcp(l) => l.accept().map(|(s, _)| Stream::Tcp(s)),
+        }
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        if let Self::Unix(_, path) = self {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Returns an error if the directory at `dir` isn't owned by the current user, or if anyone else
+/// can use it. Otherwise, another user could have made the directory beforehand, to replace the
+/// socket with their own.
+fn check_socket_dir(dir: &Path) -> Result<(), MuseStatusError> {
+    let metadata = fs::metadata(dir)?;
+
+    // SAFETY: getuid can't fail
+    if metadata.uid() != unsafe { libc::getuid() } {
+        return Err(MuseStatusError::from(BasicError {
+            message: format!(
+                "{} belongs to another user, so the socket can't be put there",
+                dir.display()
+            ),
+        }));
+    }
+
+    if metadata.mode() & 0o077 != 0 {
+        return Err(MuseStatusError::from(BasicError {
+            message: format!(
+                "{} can be used by other users (its mode is {:o}), so the socket can't be put \
+                 there; it should be 700",
+                dir.display(),
+                metadata.mode() & 0o777
+            ),
+        }));
+    }
+
+    Ok(())
+}
+
+/// Removes the socket file at `path` if no daemon is listening on it anymore. Anything at `path`
+/// that isn't a socket is left alone, and an error is returned, so that a mistyped `daemon_addr`
+/// can't delete a file.
+fn remove_stale_socket(path: &Path) -> Result<(), MuseStatusError> {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(m) => m,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+
+    if !metadata.file_type().is_socket() {
+        return Err(MuseStatusError::from(BasicError {
+            message: format!(
+                "{} already exists and isn't a socket, so the daemon can't listen there",
+                path.display()
+            ),
+        }));
+    }
+
+    if UnixStream::connect(path).is_ok() {
+        return Err(MuseStatusError::from(BasicError {
+            message: format!("another daemon is already listening on {}", path.display()),
+        }));
+    }
+
+    eprintln!("removing stale socket file at {}", path.display());
+    fs::remove_file(path)?;
+
+    Ok(())
+}
@@ -0,0 +1,393 @@
+use crate::{
+    config::UtilizationConfig,
+    errors::*,
+    format::{
+        blocks::{output::*, *},
+        Attention,
+    },
+};
+use chrono::Duration;
+use std::{fs, path::PathBuf};
+
+pub(crate) const PROC_DIR: &str = "/proc";
+const CPU_ICON: char = '\u{F061A}';
+
+/// Time spent by a CPU (or all CPUs together), in clock ticks since boot.
+#[derive(Clone, Copy, Default)]
+struct CpuTimes {
+    idle: u64,
+    total: u64,
+}
+
+impl CpuTimes {
+    /// Parses a `cpu` line from /proc/stat, like `cpu0 4705 356 584 3699 23 23 0 0 0 0`.
+    fn from_stat_line(line: &str) -> Option<Self> {
+        let values = line
+            .split_whitespace()
+            .skip(1)
+            .map(|v| v.parse::<u64>())
+            .collect::<Result<Vec<_>, _>>()
+            .ok()?;
+
+        // user, nice, system, idle, iowait, irq, softirq and steal. guest time is already
+        // counted in user and nice
+        let total = values.iter().take(8).sum();
+        let idle = values.get(3)? + values.get(4).unwrap_or(&0);
+
+        Some(Self { idle, total })
+    }
+
+    /// Returns how busy the CPU was between `earlier` and these times, from 0.0 to 1.0.
+    fn usage_since(&self, earlier: &CpuTimes) -> f32 {
+        let total = self.total.saturating_sub(earlier.total);
+        let idle = self.idle.saturating_sub(earlier.idle);
+        if total == 0 {
+            0.0
+        } else {
+            (total - idle.min(total)) as f32 / total as f32
+        }
+    }
+}
+
+/// Memory and swap sizes from /proc/meminfo, in kB.
+#[derive(Clone, Copy, Default)]
+struct MemoryInfo {
+    mem_total: u64,
+    mem_available: u64,
+    swap_total: u64,
+    swap_free: u64,
+}
+
+impl MemoryInfo {
+    fn from_meminfo(meminfo: &str) -> Self {
+        let mut info = Self::default();
+
+        // older kernels don't have MemAvailable, so it's estimated from these instead
+        let mut free_estimate = 0;
+        let mut has_available = false;
+
+        for line in meminfo.lines() {
+            let mut parts = line.split_whitespace();
+            let (key, value) = match (parts.next(), parts.next().map(str::parse::<u64>)) {
+                (Some(k), Some(Ok(v))) => (k.trim_end_matches(':'), v),
+                _ => continue,
+            };
+
+            match key {
+                "MemTotal" => info.mem_total = value,
+                "MemAvailable" => {
+                    info.mem_available = value;
+                    has_available = true;
+                }
+                "MemFree" | "Buffers" | "Cached" => free_estimate += value,
+                "SwapTotal" => info.swap_total = value,
+                "SwapFree" => info.swap_free = value,
+                _ => (),
+            }
+        }
+
+        if !has_available {
+            info.mem_available = free_estimate;
+        }
+
+        info
+    }
+
+    /// Returns how much memory is used, from 0.0 to 1.0.
+    fn memory_usage(&self) -> f32 {
+        usage_of(
+            self.mem_total - self.mem_available.min(self.mem_total),
+            self.mem_total,
+        )
+    }
+
+    /// Returns how much swap is used, from 0.0 to 1.0. Systems without swap use none of it.
+    fn swap_usage(&self) -> f32 {
+        usage_of(
+            self.swap_total - self.swap_free.min(self.swap_total),
+            self.swap_total,
+        )
+    }
+}
+
+fn usage_of(used: u64, total: u64) -> f32 {
+    if total == 0 {
+        0.0
+    } else {
+        used as f32 / total as f32
+    }
+}
+
+/// A block that shows CPU, memory and swap usage.
+pub struct UtilizationBlock {
+    id: String,
+    config: UtilizationConfig,
+
+    /// Times from the last read of /proc/stat. The first entry is for all CPUs together, and the
+    /// rest are for each core.
+    last_cpu_times: Vec<CpuTimes>,
+
+    /// CPU usage since the last read, in the same order as `last_cpu_times`.
+    cpu_usage: Vec<f32>,
+
+    memory: Option<MemoryInfo>,
+}
+
+impl UtilizationBlock {
+    /// Returns a new block with the configuration provided.
+    pub fn new(config: UtilizationConfig) -> Self {
+        Self {
+            id: String::from("utilization"),
+            config,
+            last_cpu_times: Vec::new(),
+            cpu_usage: Vec::new(),
+            memory: None,
+        }
+    }
+
+    /// Sets the id of the block, which is used as its name. By default, the id is `utilization`.
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = id.to_string();
+        self
+    }
+
+    fn proc_path(&self, file: &str) -> PathBuf {
+        PathBuf::from(&self.config.proc_root).join(file)
+    }
+
+    fn read_cpu_times(&self) -> Result<Vec<CpuTimes>, MuseStatusError> {
+        let stat = fs::read_to_string(self.proc_path("stat"))?;
+        let times: Vec<CpuTimes> = stat
+            .lines()
+            .filter(|l| l.starts_with("cpu"))
+            .filter_map(CpuTimes::from_stat_line)
+            .collect();
+
+        if times.is_empty() {
+            Err(MuseStatusError::from(String::from(
+                "no cpu lines were found in stat",
+            )))
+        } else {
+            Ok(times)
+        }
+    }
+
+    fn update_cpu(&mut self) -> Result<(), MuseStatusError> {
+        let times = self.read_cpu_times()?;
+
+        // the first read is compared with boot, so it shows the average usage since then
+        let zero = CpuTimes::default();
+        self.cpu_usage = times
+            .iter()
+            .enumerate()
+            .map(|(i, t)| t.usage_since(self.last_cpu_times.get(i).unwrap_or(&zero)))
+            .collect();
+        self.last_cpu_times = times;
+
+        Ok(())
+    }
+
+    fn update_memory(&mut self) -> Result<(), MuseStatusError> {
+        let meminfo = fs::read_to_string(self.proc_path("meminfo"))?;
+        self.memory = Some(MemoryInfo::from_meminfo(&meminfo));
+
+        Ok(())
+    }
+
+    fn attention(&self) -> Attention {
+        let cpu = self.cpu_usage.first().copied().unwrap_or(0.0);
+        let memory = self.memory.map(|m| m.memory_usage()).unwrap_or(0.0);
+
+        if cpu >= self.config.cpu_alarm || memory >= self.config.memory_alarm {
+            Attention::Alarm
+        } else if cpu >= self.config.cpu_warning || memory >= self.config.memory_warning {
+            Attention::Warning
+        } else {
+            Attention::Normal
+        }
+    }
+}
+
+impl Block for UtilizationBlock {
+    fn name(&self) -> &str {
+        &self.id
+    }
+
+    fn update(&mut self) -> Result<(), UpdateError> {
+        if let Err(e) = self.update_cpu() {
+            return Err(UpdateError {
+                block_name: self.name().to_string(),
+                message: format!("couldn't read cpu usage: {}", e),
+            });
+        }
+
+        if let Err(e) = self.update_memory() {
+            return Err(UpdateError {
+                block_name: self.name().to_string(),
+                message: format!("couldn't read memory usage: {}", e),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn next_update(&self) -> Option<NextUpdate> {
+        Some(NextUpdate::In(Duration::seconds(
+            self.config.update_interval_seconds.max(1).into(),
+        )))
+    }
+
+    fn output(&self) -> Option<BlockOutput> {
+        let total_cpu = self.cpu_usage.first()?;
+        let memory = self.memory?;
+
+        let primary_text = if self.config.per_core && self.cpu_usage.len() > 1 {
+            self.cpu_usage[1..]
+                .iter()
+                .map(|u| format!("{:.0}%", u * 100.0))
+                .collect::<Vec<_>>()
+                .join(" ")
+        } else {
+            format!("{:.0}%", total_cpu * 100.0)
+        };
+
+        let mut secondary_text = format!("Mem {:.0}%", memory.memory_usage() * 100.0);
+        if memory.swap_total > 0 && memory.swap_free < memory.swap_total {
+            secondary_text.push_str(&format!(", Swap {:.0}%", memory.swap_usage() * 100.0));
+        }
+
+        Some(BlockOutput::new(
+            self.name(),
+            Some(CPU_ICON),
+            BlockText::Pair(primary_text, secondary_text),
+            self.attention(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{pair_text, FixtureDir};
+
+    const MEMINFO: &str = "MemTotal:        1000 kB\n\
+                           MemFree:          100 kB\n\
+                           MemAvailable:     500 kB\n\
+                           SwapTotal:       1000 kB\n\
+                           SwapFree:         750 kB\n";
+
+    /// Returns a block reading from a fixture proc directory with `stat` and `meminfo` in it.
+    fn block_with(
+        stat: &str,
+        meminfo: &str,
+        config: UtilizationConfig,
+    ) -> (FixtureDir, UtilizationBlock) {
+        let proc = FixtureDir::new();
+        proc.write("stat", stat).write("meminfo", meminfo);
+
+        let block = UtilizationBlock::new(UtilizationConfig {
+            proc_root: proc.root(),
+            ..config
+        });
+
+        (proc, block)
+    }
+
+    #[test]
+    fn reads_cpu_memory_and_swap() {
+        let stat = "cpu  100 0 100 800 0 0 0 0 0 0\nintr 12345\n";
+        let (_proc, mut block) = block_with(stat, MEMINFO, Default::default());
+        block.update().unwrap();
+
+        assert_eq!(
+            pair_text(&block),
+            Some((String::from("20%"), String::from("Mem 50%, Swap 25%")))
+        );
+        assert_eq!(block.output().unwrap().attention(), &Attention::Normal);
+    }
+
+    #[test]
+    fn measures_cpu_usage_between_reads() {
+        let (proc, mut block) = block_with(
+            "cpu  100 0 100 800 0 0 0 0 0 0\n",
+            MEMINFO,
+            Default::default(),
+        );
+        block.update().unwrap();
+
+        // 1000 more ticks, 100 of them idle
+        proc.write("stat", "cpu  600 0 500 900 0 0 0 0 0 0\n");
+        block.update().unwrap();
+
+        assert_eq!(pair_text(&block).unwrap().0, "90%");
+        assert_eq!(block.output().unwrap().attention(), &Attention::Warning);
+    }
+
+    #[test]
+    fn shows_each_core() {
+        let stat = "cpu  100 0 100 800 0 0 0 0 0 0\n\
+                    cpu0 10 0 0 90 0 0 0 0 0 0\n\
+                    cpu1 40 0 10 50 0 0 0 0 0 0\n";
+        let config = UtilizationConfig {
+            per_core: true,
+            ..Default::default()
+        };
+        let (_proc, mut block) = block_with(stat, MEMINFO, config);
+        block.update().unwrap();
+
+        assert_eq!(pair_text(&block).unwrap().0, "10% 50%");
+    }
+
+    #[test]
+    fn hides_unused_swap_and_estimates_available_memory() {
+        // older kernels don't have MemAvailable
+        let meminfo = "MemTotal: 1000 kB\n\
+                       MemFree: 200 kB\n\
+                       Buffers: 50 kB\n\
+                       Cached: 150 kB\n\
+                       SwapTotal: 0 kB\n\
+                       SwapFree: 0 kB\n";
+        let (_proc, mut block) =
+            block_with("cpu  0 0 0 100 0 0 0 0\n", meminfo, Default::default());
+        block.update().unwrap();
+
+        assert_eq!(pair_text(&block).unwrap().1, "Mem 60%");
+    }
+
+    #[test]
+    fn maps_thresholds_to_attention() {
+        let cases = [
+            // cpu busy ticks out of 100, memory available out of 1000
+            (10, 500, Attention::Normal),
+            (80, 500, Attention::Warning),
+            (96, 500, Attention::Alarm),
+            (10, 150, Attention::Warning),
+            (10, 40, Attention::Alarm),
+            (80, 40, Attention::Alarm),
+        ];
+
+        for (busy, available, expected) in cases {
+            let stat = format!("cpu  {} 0 0 {} 0 0 0 0\n", busy, 100 - busy);
+            let meminfo = format!("MemTotal: 1000 kB\nMemAvailable: {} kB\n", available);
+            let (_proc, mut block) = block_with(&stat, &meminfo, Default::default());
+            block.update().unwrap();
+
+            assert_eq!(
+                block.output().unwrap().attention(),
+                &expected,
+                "cpu {}%, {} kB available",
+                busy,
+                available
+            );
+        }
+    }
+
+    #[test]
+    fn fails_without_stat() {
+        let (proc, mut block) = block_with("", MEMINFO, Default::default());
+        proc.remove("stat");
+
+        assert!(block.update().is_err());
+        assert!(block.output().is_none());
+    }
+}
@@ -2,7 +2,8 @@ use crate::{
     errors::*,
     format::{color::RGBA, Mode},
 };
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use std::{borrow::Cow, fs};
 
 /// Returns a number in a file
@@ -10,6 +11,71 @@ pub fn get_int_from_file(filepath: &Path) -> Result<i32, MuseStatusError> {
     Ok(fs::read_to_string(filepath)?.trim().parse::<i32>()?)
 }
 
+/// Returns the paths of the entries in `dir`, sorted. Returns nothing if `dir` can't be read.
+pub fn sorted_dir_entries(dir: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(r) => r.filter_map(|e| e.ok()).map(|e| e.path()).collect(),
+        Err(_) => return Vec::new(),
+    };
+    entries.sort();
+    entries
+}
+
+/// Returns true if `path` is the directory of a process in procfs, like `/proc/1234`. Entries
+/// like `/proc/self` and `/proc/thread-self` aren't processes of their own.
+pub fn is_pid_dir(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+        .unwrap_or(false)
+}
+
+/// Parses a duration like `90`, `90s`, `4m` or `1h30m`. A number without a unit is in seconds.
+pub fn parse_duration(s: &str) -> Result<Duration, MuseStatusError> {
+    let invalid = || {
+        MuseStatusError::from(BasicError {
+            message: format!(
+                "`{}` isn't a valid duration (try something like `4m` or `1h30m`)",
+                s
+            ),
+        })
+    };
+
+    if s.trim().is_empty() {
+        return Err(invalid());
+    }
+
+    let mut total = 0.0;
+    let mut number = String::new();
+    for c in s.trim().chars() {
+        if c.is_ascii_digit() || c == '.' {
+            number.push(c);
+            continue;
+        }
+
+        let multiplier = match c {
+            'h' => 3600.0,
+            'm' => 60.0,
+            's' => 1.0,
+            _ => return Err(invalid()),
+        };
+        total += number.parse::<f64>().map_err(|_| invalid())? * multiplier;
+        number.clear();
+    }
+
+    // trailing digits without a unit are seconds
+    if !number.is_empty() {
+        total += number.parse::<f64>().map_err(|_| invalid())?;
+    }
+
+    Duration::try_from_secs_f64(total).map_err(|_| invalid())
+}
+
+/// Quotes `s` for a POSIX shell, so that it's passed as one word with nothing in it interpreted.
+pub fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
 /// Returns the reuslt of a concave-down cubic function
 pub fn cubic_ease_arc(mut x: f32) -> f32 {
     x *= 2.0;
@@ -48,6 +114,27 @@ pub fn make_pango_string(text: &str, rgba: Option<RGBA>, font: Option<&str>) ->
     }
 }
 
+/// Creates a lemonbar string from the text. The text is colored with `rgba` if it's `Some` and
+/// uses the font at `font_index` (as passed to lemonbar with `-f`, starting at 1) if it is `Some`.
+pub fn make_lemonbar_string(text: &str, rgba: Option<RGBA>, font_index: Option<u8>) -> String {
+    let mut s = lemonbar_escape(text);
+
+    if let Some(i) = font_index {
+        s = format!("%{{T{}}}{}%{{T-}}", i, s);
+    }
+
+    if let Some(c) = rgba {
+        s = format!("%{{F#{}}}{}%{{F-}}", c.hex_string(Mode::Lemonbar), s);
+    }
+
+    s
+}
+
+/// Escapes percent signs, which lemonbar would otherwise read as the start of a formatting tag.
+pub fn lemonbar_escape(s: &str) -> String {
+    s.replace('%', "%%")
+}
+
 fn xml_escape(s: &str) -> Cow<str> {
     xml::escape::escape_str_attribute(s)
 }
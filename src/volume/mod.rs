@@ -1,6 +1,7 @@
 use crate::errors::*;
+use crate::format::animation::Animation;
 use crate::format::blocks::output::{BlockOutput, BlockText};
-use crate::format::blocks::{Block, NextUpdate};
+use crate::format::blocks::{click::MouseButton, Block, ClickEvent, NextUpdate};
 use crate::format::Attention;
 use std::process::Command;
 
@@ -21,10 +22,28 @@ impl Default for Volume {
 }
 
 /// VolumeBlock provides information for the system's audio volume. Requires `amixer`.
-#[derive(Default)]
 pub struct VolumeBlock {
+    id: String,
     volume_sink: Option<String>,
     current_volume: Volume,
+
+    /// The volume before the last update, which the shown volume counts up or down from.
+    previous_volume: Volume,
+
+    /// Whether the volume is shown as a slider instead of a percentage.
+    slider: bool,
+}
+
+impl Default for VolumeBlock {
+    fn default() -> Self {
+        Self {
+            id: String::from("volume"),
+            volume_sink: None,
+            current_volume: Volume::default(),
+            previous_volume: Volume::default(),
+            slider: false,
+        }
+    }
 }
 
 impl VolumeBlock {
@@ -36,6 +55,19 @@ impl VolumeBlock {
         }
     }
 
+    /// Sets the id of the block, which is used as its name. By default, the id is `volume`.
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = id.to_string();
+        self
+    }
+
+    /// Sets whether the volume is shown as a slider instead of a percentage. By default, it's a
+    /// percentage.
+    pub fn with_slider(mut self, slider: bool) -> Self {
+        self.slider = slider;
+        self
+    }
+
     const MAX_WAIT_SECONDS: u64 = 30;
 
     /// Gets the system volume from the `pamixer` command
@@ -68,15 +100,15 @@ impl VolumeBlock {
                 })
             })
             .map_err(|e| UpdateError {
-                block_name: "volume".to_string(),
+                block_name: self.name().to_string(),
                 message: format!("{}", e),
             })?
             .map_err(|e| UpdateError {
-                block_name: "volume".to_string(),
+                block_name: self.name().to_string(),
                 message: format!("{}", e),
             })?
             .map_err(|e| UpdateError {
-                block_name: "volume".to_string(),
+                block_name: self.name().to_string(),
                 message: format!("{}", e),
             })?;
 
@@ -110,15 +142,15 @@ impl VolumeBlock {
                     })
                 })
                 .map_err(|e| UpdateError {
-                    block_name: "volume".to_string(),
+                    block_name: self.name().to_string(),
                     message: format!("{}", e),
                 })?
                 .map_err(|e| UpdateError {
-                    block_name: "volume".to_string(),
+                    block_name: self.name().to_string(),
                     message: format!("{}", e),
                 })?
                 .map_err(|e| UpdateError {
-                    block_name: "volume".to_string(),
+                    block_name: self.name().to_string(),
                     message: format!("{}", e),
                 })?;
 
@@ -136,11 +168,11 @@ impl VolumeBlock {
                     .map(|info| info.lines().last().map(|last_line| last_line.to_string()))
             })
             .map_err(|e| UpdateError {
-                block_name: "volume".to_string(),
+                block_name: self.name().to_string(),
                 message: format!("{}", e),
             })?
             .map_err(|e| UpdateError {
-                block_name: "volume".to_string(),
+                block_name: self.name().to_string(),
                 message: format!("{}", e),
             })?;
 
@@ -161,7 +193,7 @@ impl VolumeBlock {
 
                         let current_volume =
                             raw_percent.parse::<i32>().map_err(|e| UpdateError {
-                                block_name: String::from("volume"),
+                                block_name: self.name().to_string(),
                                 message: format!(
                                     "couldn't parse volume from `{}`: {}",
                                     raw_percent, e
@@ -172,7 +204,7 @@ impl VolumeBlock {
                     }
                 }
                 None => Err(UpdateError {
-                    block_name: String::from("volume"),
+                    block_name: self.name().to_string(),
                     message: String::from(
                         "couldn't find square bracket delimiter in amixer output",
                     ),
@@ -180,12 +212,53 @@ impl VolumeBlock {
             }
         } else {
             Err(UpdateError {
-                block_name: String::from("volume"),
+                block_name: self.name().to_string(),
                 message: String::from("couldn't get any output to parse from (amixer)"),
             })
         }
     }
 
+    /// How much the volume changes with each scroll, in percent.
+    const SCROLL_STEP: i32 = 5;
+
+    /// Changes the volume with `pamixer`, falling back to `amixer` if that fails. `pamixer_args`
+    /// and `amixer_args` are the arguments that do the same thing in each command.
+    fn set_volume(&self, pamixer_args: &[&str], amixer_args: &[&str]) -> Result<(), UpdateError> {
+        let mut args = pamixer_args.to_vec();
+        if let Some(sink) = &self.volume_sink {
+            args.push("--sink");
+            args.push(sink);
+        }
+
+        let pamixer_ok = Command::new("pamixer")
+            .args(&args)
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false);
+
+        if pamixer_ok {
+            return Ok(());
+        }
+
+        let amixer_status = Command::new("amixer")
+            .args(["set", "Master"])
+            .args(amixer_args)
+            .status()
+            .map_err(|e| UpdateError {
+                block_name: self.name().to_string(),
+                message: format!("couldn't change volume: {}", e),
+            })?;
+
+        if amixer_status.success() {
+            Ok(())
+        } else {
+            Err(UpdateError {
+                block_name: self.name().to_string(),
+                message: String::from("couldn't change volume with pamixer or amixer"),
+            })
+        }
+    }
+
     fn get_icon(&self) -> char {
         match self.current_volume {
             Volume::On(0) => ZERO_ICON,
@@ -198,10 +271,16 @@ impl VolumeBlock {
         }
     }
 
-    fn get_text(&self) -> String {
+    fn get_text(&self) -> BlockText {
         match self.current_volume {
-            Volume::Off | Volume::On(0) => String::from("Muted"),
-            Volume::On(x) => format!("{}%", x),
+            Volume::Off | Volume::On(0) => BlockText::Single(String::from("Muted")),
+            Volume::On(x) if self.slider => BlockText::Progress {
+                value: x as f32,
+                min: 0.0,
+                max: 100.0,
+                label: None,
+            },
+            Volume::On(x) => BlockText::Single(format!("{}%", x)),
         }
     }
 
@@ -211,7 +290,7 @@ impl VolumeBlock {
 impl Block for VolumeBlock {
     fn update(&mut self) -> Result<(), UpdateError> {
         let mut wait_time_seconds = 1;
-        self.current_volume = loop {
+        let new_volume = loop {
             // try `pamixer` first
             match self.volume_from_pamixer() {
                 Ok(vol) => break vol,
@@ -240,28 +319,61 @@ impl Block for VolumeBlock {
                 wait_time_seconds = Self::MAX_WAIT_SECONDS.min(wait_time_seconds * 2);
             }
         };
+        self.previous_volume = std::mem::replace(&mut self.current_volume, new_volume);
 
         Ok(())
     }
 
     fn name(&self) -> &str {
-        "volume"
+        &self.id
     }
 
     fn next_update(&self) -> Option<NextUpdate> {
         None
     }
 
+    // scrolling changes the volume and left click toggles mute
+    fn click(&mut self, event: &ClickEvent) -> Result<(), UpdateError> {
+        let step = Self::SCROLL_STEP.to_string();
+        match event.button {
+            MouseButton::ScrollUp => self.set_volume(&["-i", &step], &[&format!("{}%+", step)])?,
+            MouseButton::ScrollDown => {
+                self.set_volume(&["-d", &step], &[&format!("{}%-", step)])?
+            }
+            MouseButton::Left => self.set_volume(&["-t"], &["toggle"])?,
+            _ => return Ok(()),
+        }
+
+        self.update()
+    }
+
+    fn click_buttons(&self) -> &'static [MouseButton] {
+        &[
+            MouseButton::Left,
+            MouseButton::ScrollUp,
+            MouseButton::ScrollDown,
+        ]
+    }
+
     fn output(&self) -> Option<BlockOutput> {
-        Some(BlockOutput::new(
+        let output = BlockOutput::new(
             self.name(),
             Some(self.get_icon()),
-            BlockText::Single(self.get_text()),
+            self.get_text(),
             Attention::Dim,
-        ))
+        );
+
+        // count to the new volume from the old one
+        match (&self.previous_volume, &self.current_volume) {
+            (Volume::On(old), Volume::On(new)) if old != new && *new != 0 => {
+                Some(output.with_animation(Animation::tween(*old as f32, *new as f32)))
+            }
+            _ => Some(output),
+        }
     }
 }
 
-const VOLUME_ICONS: [char; 3] = ['\u{F057F}', '\u{F0580}', '\u{F057E}'];
+/// Icons for each level of volume, from quietest to loudest.
+pub(crate) const VOLUME_ICONS: [char; 3] = ['\u{F057F}', '\u{F0580}', '\u{F057E}'];
 const MUTE_ICON: char = '\u{F0581}';
 const ZERO_ICON: char = '\u{F0E08}';
@@ -13,7 +13,7 @@ use serde::{Deserialize, Serialize};
 use structs::*;
 
 /// Type of units to use when reporting locale-specific measurements.
-#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Units {
     /// Freedom units.
@@ -35,6 +35,7 @@ impl Units {
 /// WeatherBlock returns information about the weather around the user's current location.
 /// OpenWeatherMap and IPStack are used for weather and location respectively.
 pub struct WeatherBlock {
+    id: String,
     config: WeatherConfig,
 
     current_report: Option<FullWeatherReport>,
@@ -51,6 +52,7 @@ impl WeatherBlock {
     /// Creates a new weather block.
     pub fn new(config: WeatherConfig) -> Self {
         Self {
+            id: String::from("weather"),
             config,
 
             current_report: None,
@@ -58,6 +60,12 @@ impl WeatherBlock {
         }
     }
 
+    /// Sets the id of the block, which is used as its name. By default, the id is `weather`.
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = id.to_string();
+        self
+    }
+
     /// Creates a new weather block, but with a custom location.
     pub fn new_with_location(config: WeatherConfig, location: WeatherLocation) -> Self {
         let mut w = Self::new(config);
@@ -175,7 +183,7 @@ impl Block for WeatherBlock {
     }
 
     fn name(&self) -> &str {
-        "weather"
+        &self.id
     }
 
     fn output(&self) -> Option<BlockOutput> {